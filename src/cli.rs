@@ -0,0 +1,1319 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::format::{ListFormat, render, render_one};
+use crate::git::GitIdentity;
+use crate::help::{show_command_help, show_help};
+use crate::init::init_script;
+use crate::node::{self, Installed, Resolution};
+use crate::package;
+use crate::project::{
+    Profile, Project, SortMode, absolute_path, add_project, find_project, find_project_containing,
+    pin_project, sort_projects, unpin_project,
+};
+use crate::scan::{DEFAULT_DEPTH, find_projects, find_repositories};
+use crate::shell::{Run, is_env_name, open_project, shell_here};
+use crate::store::{load_projects, save_projects, set_store_override};
+use crate::tui::{AfterOpen, AppOptions, OpenIn, run_app};
+use crate::updater::check_for_update;
+use crate::version;
+
+pub enum Command {
+    Dashboard,
+    Help(Option<String>),
+    Add {
+        path: Option<PathBuf>,
+        name: Option<String>,
+    },
+    Scan {
+        dir: Option<PathBuf>,
+        depth: usize,
+        yes: bool,
+    },
+    Remove {
+        project: String,
+    },
+    Prune {
+        dry_run: bool,
+        yes: bool,
+    },
+    Rename {
+        project: String,
+        new_name: String,
+    },
+    Pin {
+        project: String,
+        position: Option<u32>,
+    },
+    Unpin {
+        project: String,
+    },
+    Tag {
+        project: String,
+        tags: Vec<String>,
+    },
+    Untag {
+        project: String,
+        tags: Vec<String>,
+    },
+    List {
+        query: Option<String>,
+        format: ListFormat,
+        sort: SortMode,
+    },
+    Open {
+        project: String,
+        profile: Option<String>,
+        package: Option<String>,
+        /// Replace nodash with the shell instead of opening a terminal.
+        here: bool,
+    },
+    Trust {
+        project: String,
+    },
+    /// Lists, shows, sets or removes launch profiles depending on which
+    /// arguments are given.
+    Profile {
+        project: String,
+        name: Option<String>,
+        command: Option<String>,
+        env: BTreeMap<String, String>,
+        remove: bool,
+    },
+    Pick {
+        query: Option<String>,
+        format: ListFormat,
+        select_one: bool,
+        sort: SortMode,
+    },
+    Which {
+        path: Option<PathBuf>,
+        format: ListFormat,
+    },
+    Init {
+        shell: String,
+        cmd: String,
+        hook: bool,
+    },
+    Update,
+    Version,
+}
+
+struct Flag {
+    long: &'static str,
+    short: Option<char>,
+    takes_value: bool,
+}
+
+const ADD_FLAGS: &[Flag] = &[Flag {
+    long: "name",
+    short: Some('n'),
+    takes_value: true,
+}];
+
+const SCAN_FLAGS: &[Flag] = &[
+    Flag {
+        long: "depth",
+        short: Some('d'),
+        takes_value: true,
+    },
+    Flag {
+        long: "yes",
+        short: Some('y'),
+        takes_value: false,
+    },
+];
+
+const PRUNE_FLAGS: &[Flag] = &[
+    Flag {
+        long: "dry-run",
+        short: Some('n'),
+        takes_value: false,
+    },
+    Flag {
+        long: "yes",
+        short: Some('y'),
+        takes_value: false,
+    },
+];
+
+const SORT_FLAG: Flag = Flag {
+    long: "sort",
+    short: Some('s'),
+    takes_value: true,
+};
+
+const JSON_FLAG: Flag = Flag {
+    long: "json",
+    short: None,
+    takes_value: false,
+};
+
+const FORMAT_FLAG: Flag = Flag {
+    long: "format",
+    short: Some('f'),
+    takes_value: true,
+};
+
+const LIST_FLAGS: &[Flag] = &[SORT_FLAG, JSON_FLAG, FORMAT_FLAG];
+
+const WHICH_FLAGS: &[Flag] = &[JSON_FLAG, FORMAT_FLAG];
+
+const PICK_FLAGS: &[Flag] = &[
+    SORT_FLAG,
+    JSON_FLAG,
+    FORMAT_FLAG,
+    Flag {
+        long: "select-1",
+        short: Some('1'),
+        takes_value: false,
+    },
+];
+
+const PIN_FLAGS: &[Flag] = &[Flag {
+    long: "position",
+    short: Some('p'),
+    takes_value: true,
+}];
+
+const OPEN_FLAGS: &[Flag] = &[
+    Flag {
+        long: "profile",
+        short: Some('p'),
+        takes_value: true,
+    },
+    Flag {
+        long: "package",
+        short: None,
+        takes_value: true,
+    },
+    Flag {
+        long: "here",
+        short: None,
+        takes_value: false,
+    },
+];
+
+const PROFILE_FLAGS: &[Flag] = &[
+    Flag {
+        long: "env",
+        short: Some('e'),
+        takes_value: true,
+    },
+    Flag {
+        long: "remove",
+        short: None,
+        takes_value: false,
+    },
+];
+
+const INIT_FLAGS: &[Flag] = &[
+    Flag {
+        long: "cmd",
+        short: None,
+        takes_value: true,
+    },
+    Flag {
+        long: "hook",
+        short: None,
+        takes_value: false,
+    },
+];
+
+/// Positional arguments and flags of a single subcommand invocation.
+struct Parsed {
+    positional: Vec<String>,
+    flags: Vec<(&'static str, Option<String>)>,
+}
+
+impl Parsed {
+    fn value(&self, long: &str) -> Option<String> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == long)
+            .and_then(|(_, value)| value.clone())
+    }
+
+    /// Every value of a flag that may be repeated, in order.
+    fn values(&self, long: &str) -> Vec<String> {
+        self.flags
+            .iter()
+            .filter(|(name, _)| *name == long)
+            .filter_map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    fn has(&self, long: &str) -> bool {
+        self.flags.iter().any(|(name, _)| *name == long)
+    }
+
+    /// Output format selected by `--json` / `--format`, for list-like commands.
+    fn list_format(&self, default: ListFormat) -> io::Result<ListFormat> {
+        match (self.has("json"), self.value("format")) {
+            (true, Some(_)) => Err(usage_error(
+                "'--json' cannot be combined with '--format'".to_string(),
+            )),
+            (true, None) => Ok(ListFormat::Json),
+            (false, Some(format)) => Ok(ListFormat::parse(&format)),
+            (false, None) => Ok(default),
+        }
+    }
+
+    fn sort_mode(&self) -> io::Result<SortMode> {
+        match self.value("sort") {
+            Some(mode) => SortMode::parse(&mode),
+            None => Ok(SortMode::Frecency),
+        }
+    }
+
+    fn expect_positional(&self, command: &str, names: &[&str]) -> io::Result<()> {
+        if self.positional.len() < names.len() {
+            return Err(usage_error(format!(
+                "'{}' is missing <{}>",
+                command,
+                names[self.positional.len()]
+            )));
+        }
+        if self.positional.len() > names.len() {
+            return Err(usage_error(format!(
+                "Unexpected argument '{}' for '{}'",
+                self.positional[names.len()],
+                command
+            )));
+        }
+        Ok(())
+    }
+}
+
+// `KEY=VALUE` pairs given with `--env`
+fn parse_env(pairs: Vec<String>) -> io::Result<BTreeMap<String, String>> {
+    let mut env = BTreeMap::new();
+    for pair in pairs {
+        let valid = pair.split_once('=').filter(|(key, _)| is_env_name(key));
+        let Some((key, value)) = valid else {
+            return Err(usage_error(format!(
+                "Invalid variable '{}', expected KEY=VALUE",
+                pair
+            )));
+        };
+        env.insert(key.to_string(), value.to_string());
+    }
+    Ok(env)
+}
+
+fn usage_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn parse_flags(command: &str, args: &[String], spec: &[Flag]) -> io::Result<Parsed> {
+    let mut parsed = Parsed {
+        positional: Vec::new(),
+        flags: Vec::new(),
+    };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            parsed.positional.extend(iter.by_ref().cloned());
+            break;
+        }
+
+        let (flag, inline_value) = if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            (spec.iter().find(|f| f.long == name), value)
+        } else if arg.len() == 2 && arg.starts_with('-') {
+            let short = arg.chars().nth(1);
+            (spec.iter().find(|f| f.short == short), None)
+        } else {
+            parsed.positional.push(arg.clone());
+            continue;
+        };
+
+        let Some(flag) = flag else {
+            return Err(usage_error(format!(
+                "Unknown option '{}' for '{}'",
+                arg, command
+            )));
+        };
+
+        let value = if flag.takes_value {
+            match inline_value.or_else(|| iter.next().cloned()) {
+                Some(value) => Some(value),
+                None => {
+                    return Err(usage_error(format!(
+                        "Option '--{}' requires a value",
+                        flag.long
+                    )));
+                }
+            }
+        } else {
+            None
+        };
+        parsed.flags.push((flag.long, value));
+    }
+
+    Ok(parsed)
+}
+
+// Pulls the global `--store PATH` option out of the arguments, wherever it
+// appears before `--`.
+fn take_store_option(args: &[String]) -> io::Result<Vec<String>> {
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            remaining.push(arg.clone());
+            remaining.extend(iter.by_ref().cloned());
+            break;
+        }
+        let path = if arg == "--store" {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| usage_error("Option '--store' requires a value".to_string()))?
+        } else if let Some(path) = arg.strip_prefix("--store=") {
+            path.to_string()
+        } else {
+            remaining.push(arg.clone());
+            continue;
+        };
+        set_store_override(absolute_path(Path::new(&path))?);
+    }
+    Ok(remaining)
+}
+
+/// Turns the raw process arguments (without the binary name) into a command.
+pub fn parse_args(args: &[String]) -> io::Result<Command> {
+    let args = take_store_option(args)?;
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Command::Dashboard);
+    };
+
+    // Anything after `--` belongs to the command being stored
+    if rest
+        .iter()
+        .take_while(|a| *a != "--")
+        .any(|a| a == "--help" || a == "-h")
+    {
+        return Ok(Command::Help(Some(command.clone())));
+    }
+
+    match command.as_str() {
+        "help" | "--help" | "-h" => Ok(Command::Help(rest.first().cloned())),
+        "version" | "--version" | "-v" => Ok(Command::Version),
+        "update" => {
+            parse_flags(command, rest, &[])?.expect_positional(command, &[])?;
+            Ok(Command::Update)
+        }
+        "add" => {
+            let parsed = parse_flags(command, rest, ADD_FLAGS)?;
+            if parsed.positional.len() > 1 {
+                parsed.expect_positional(command, &["PATH"])?;
+            }
+            Ok(Command::Add {
+                path: parsed.positional.first().map(PathBuf::from),
+                name: parsed.value("name"),
+            })
+        }
+        "scan" => {
+            let parsed = parse_flags(command, rest, SCAN_FLAGS)?;
+            if parsed.positional.len() > 1 {
+                parsed.expect_positional(command, &["DIR"])?;
+            }
+            let depth = match parsed.value("depth") {
+                Some(depth) => depth.parse().map_err(|_| {
+                    usage_error(format!("Invalid depth '{}', expected a number", depth))
+                })?,
+                None => DEFAULT_DEPTH,
+            };
+            Ok(Command::Scan {
+                dir: parsed.positional.first().map(PathBuf::from),
+                depth,
+                yes: parsed.has("yes"),
+            })
+        }
+        "prune" => {
+            let parsed = parse_flags(command, rest, PRUNE_FLAGS)?;
+            parsed.expect_positional(command, &[])?;
+            Ok(Command::Prune {
+                dry_run: parsed.has("dry-run"),
+                yes: parsed.has("yes"),
+            })
+        }
+        "remove" | "rm" => {
+            let mut parsed = parse_flags(command, rest, &[])?;
+            parsed.expect_positional(command, &["PROJECT"])?;
+            Ok(Command::Remove {
+                project: parsed.positional.remove(0),
+            })
+        }
+        "trust" => {
+            let mut parsed = parse_flags(command, rest, &[])?;
+            parsed.expect_positional(command, &["PROJECT"])?;
+            Ok(Command::Trust {
+                project: parsed.positional.remove(0),
+            })
+        }
+        "rename" | "mv" => {
+            let mut parsed = parse_flags(command, rest, &[])?;
+            parsed.expect_positional(command, &["PROJECT", "NEW_NAME"])?;
+            let new_name = parsed.positional.remove(1);
+            Ok(Command::Rename {
+                project: parsed.positional.remove(0),
+                new_name,
+            })
+        }
+        "pin" => {
+            let mut parsed = parse_flags(command, rest, PIN_FLAGS)?;
+            parsed.expect_positional(command, &["PROJECT"])?;
+            let position = match parsed.value("position") {
+                Some(value) => Some(value.parse::<u32>().ok().filter(|&n| n > 0).ok_or_else(
+                    || usage_error(format!("Invalid position '{}', expected 1 or more", value)),
+                )?),
+                None => None,
+            };
+            Ok(Command::Pin {
+                project: parsed.positional.remove(0),
+                position,
+            })
+        }
+        "unpin" => {
+            let mut parsed = parse_flags(command, rest, &[])?;
+            parsed.expect_positional(command, &["PROJECT"])?;
+            Ok(Command::Unpin {
+                project: parsed.positional.remove(0),
+            })
+        }
+        "tag" | "untag" => {
+            let mut parsed = parse_flags(command, rest, &[])?;
+            if parsed.positional.len() < 2 {
+                parsed.expect_positional(command, &["PROJECT", "TAG"])?;
+            }
+            let project = parsed.positional.remove(0);
+            let tags = parsed.positional;
+            Ok(if command == "tag" {
+                Command::Tag { project, tags }
+            } else {
+                Command::Untag { project, tags }
+            })
+        }
+        "list" | "ls" => {
+            let parsed = parse_flags(command, rest, LIST_FLAGS)?;
+            if parsed.positional.len() > 1 {
+                parsed.expect_positional(command, &["QUERY"])?;
+            }
+            Ok(Command::List {
+                query: parsed.positional.first().cloned(),
+                format: parsed.list_format(ListFormat::Tsv)?,
+                sort: parsed.sort_mode()?,
+            })
+        }
+        "pick" => {
+            let parsed = parse_flags(command, rest, PICK_FLAGS)?;
+            // Several words form one query so `nd my api` works unquoted
+            let query = parsed.positional.join(" ");
+            Ok(Command::Pick {
+                query: (!query.is_empty()).then_some(query),
+                format: parsed.list_format(ListFormat::Template("{path}".to_string()))?,
+                select_one: parsed.has("select-1"),
+                sort: parsed.sort_mode()?,
+            })
+        }
+        "which" => {
+            let parsed = parse_flags(command, rest, WHICH_FLAGS)?;
+            if parsed.positional.len() > 1 {
+                parsed.expect_positional(command, &["PATH"])?;
+            }
+            Ok(Command::Which {
+                path: parsed.positional.first().map(PathBuf::from),
+                format: parsed.list_format(ListFormat::Template("{path}".to_string()))?,
+            })
+        }
+        "init" => {
+            let mut parsed = parse_flags(command, rest, INIT_FLAGS)?;
+            parsed.expect_positional(command, &["SHELL"])?;
+            Ok(Command::Init {
+                shell: parsed.positional.remove(0),
+                cmd: parsed.value("cmd").unwrap_or_else(|| "nd".to_string()),
+                hook: parsed.has("hook"),
+            })
+        }
+        "open" => {
+            let mut parsed = parse_flags(command, rest, OPEN_FLAGS)?;
+            parsed.expect_positional(command, &["PROJECT"])?;
+            Ok(Command::Open {
+                project: parsed.positional.remove(0),
+                profile: parsed.value("profile"),
+                package: parsed.value("package"),
+                here: parsed.has("here"),
+            })
+        }
+        "profile" => {
+            let parsed = parse_flags(command, rest, PROFILE_FLAGS)?;
+            let env = parse_env(parsed.values("env"))?;
+            let remove = parsed.has("remove");
+            let mut positional = parsed.positional.into_iter();
+            let project = positional
+                .next()
+                .ok_or_else(|| usage_error(format!("'{}' is missing <PROJECT>", command)))?;
+            let name = positional.next();
+            // The rest is the command, so it can be given unquoted after `--`
+            let words: Vec<String> = positional.collect();
+            let command_line = (!words.is_empty()).then(|| words.join(" "));
+
+            if name.is_none() && (command_line.is_some() || !env.is_empty() || remove) {
+                return Err(usage_error(format!("'{}' is missing <NAME>", command)));
+            }
+            if remove && (command_line.is_some() || !env.is_empty()) {
+                return Err(usage_error(
+                    "'--remove' cannot be combined with a command or '--env'".to_string(),
+                ));
+            }
+            Ok(Command::Profile {
+                project,
+                name,
+                command: command_line,
+                env,
+                remove,
+            })
+        }
+        _ => Err(usage_error(format!("Unknown command: {}", command))),
+    }
+}
+
+pub fn run(command: Command) -> io::Result<()> {
+    match command {
+        Command::Dashboard => {
+            let mut projects = load_projects()?;
+            let options = AppOptions {
+                query: String::new(),
+                sort: SortMode::Frecency,
+                scripts: true,
+                here: true,
+            };
+            let mut here = None;
+            run_app(
+                io::stdout(),
+                &mut projects,
+                options,
+                |p, dir, run, open_in| match open_in {
+                    OpenIn::NewTerminal => open_project(p, dir, run).map(|()| AfterOpen::Stay),
+                    OpenIn::Here => {
+                        here = Some(shell_here(p, dir, run)?);
+                        Ok(AfterOpen::Exit)
+                    }
+                },
+            )?;
+            save_projects(&mut projects)?;
+            // The dashboard has restored the terminal, so the shell gets it
+            if let Some(mut shell) = here {
+                return Err(exec_shell(&mut shell));
+            }
+        }
+        Command::Help(None) => show_help(),
+        Command::Help(Some(command)) => show_command_help(&command)?,
+        Command::Version => println!("nodash version: {}", version::VERSION),
+        Command::Update => check_for_update()?,
+        Command::Pick {
+            query,
+            format,
+            select_one,
+            sort,
+        } => {
+            let mut projects = load_projects()?;
+            let mut picked = None;
+
+            if select_one && let Some(query) = &query {
+                let mut matching = projects.iter_mut().filter(|p| p.matches_query(query));
+                if let (Some(only), None) = (matching.next(), matching.next()) {
+                    only.record_open();
+                    picked = Some(only.clone());
+                }
+            }
+            if picked.is_some() {
+                save_projects(&mut projects)?;
+            } else {
+                let options = AppOptions {
+                    query: query.unwrap_or_default(),
+                    sort,
+                    scripts: false,
+                    here: false,
+                };
+                run_app(io::stderr(), &mut projects, options, |p, dir, _, _| {
+                    p.record_open();
+                    let mut choice = p.clone();
+                    // A workspace package is picked by its directory
+                    if let Some(dir) = dir {
+                        choice.path = dir.to_path_buf();
+                    }
+                    picked = Some(choice);
+                    Ok(AfterOpen::Exit)
+                })?;
+                save_projects(&mut projects)?;
+            }
+
+            // A cancelled pick must fail so `cd "$(nodash pick)"` stays put
+            let picked = picked
+                .ok_or_else(|| io::Error::new(io::ErrorKind::Interrupted, "No project picked"))?;
+            write_stdout(&render_one(&picked, &format)?)?;
+        }
+        Command::Which { path, format } => {
+            let path = match path {
+                Some(path) => absolute_path(&path)?,
+                None => std::env::current_dir()?,
+            };
+            let projects = load_projects()?;
+            let idx = find_project_containing(&projects, &path).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} is not inside a nodash project", path.display()),
+                )
+            })?;
+            write_stdout(&render_one(&projects[idx], &format)?)?;
+        }
+        Command::Init { shell, cmd, hook } => print!("{}", init_script(&shell, &cmd, hook)?),
+        Command::Scan { dir, depth, yes } => {
+            let dir = match dir {
+                Some(dir) => absolute_path(&dir)?,
+                None => std::env::current_dir()?,
+            };
+            scan(&dir, depth, yes)?;
+        }
+        Command::Add { path, name } => {
+            let path = match path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            add(&path, name.as_deref())?;
+        }
+        Command::Remove { project } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let removed = projects.remove(idx);
+            save_projects(&mut projects)?;
+            println!("🗑️  Removed '{}' from nodash", removed.name);
+        }
+        Command::Prune { dry_run, yes } => prune(dry_run, yes)?,
+        Command::Trust { project } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let p = &mut projects[idx];
+            // Trust what is on disk now, not what was read at the last open
+            p.detect_tooling()?;
+            let Some(manifest) = p.manifest.clone() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("'{}' has no nodash.toml", p.name),
+                ));
+            };
+            for file in &manifest.env_files {
+                println!("   env file: {}", file);
+            }
+            for hook in &manifest.pre_open {
+                println!("   pre_open: {}", hook);
+            }
+            p.trusted_manifest = Some(manifest);
+            println!("🔓 Trusted nodash.toml of '{}'", p.name);
+            save_projects(&mut projects)?;
+        }
+        Command::Rename { project, new_name } => {
+            let new_name = new_name.trim();
+            if new_name.is_empty() {
+                return Err(usage_error("Project name cannot be empty".to_string()));
+            }
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            if projects
+                .iter()
+                .enumerate()
+                .any(|(i, p)| i != idx && p.name == new_name)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("A project named '{}' already exists", new_name),
+                ));
+            }
+            let old_name = std::mem::replace(&mut projects[idx].name, new_name.to_string());
+            save_projects(&mut projects)?;
+            println!("✏️  Renamed '{}' to '{}'", old_name, new_name);
+        }
+        Command::Pin { project, position } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            pin_project(&mut projects, idx, position);
+            let p = projects[idx].clone();
+            save_projects(&mut projects)?;
+            println!(
+                "📌 Pinned '{}' at position {}",
+                p.name,
+                p.pinned.unwrap_or_default()
+            );
+        }
+        Command::Unpin { project } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            if projects[idx].pinned.is_none() {
+                println!("'{}' is not pinned", projects[idx].name);
+                return Ok(());
+            }
+            unpin_project(&mut projects, idx);
+            let name = projects[idx].name.clone();
+            save_projects(&mut projects)?;
+            println!("Unpinned '{}'", name);
+        }
+        Command::Profile {
+            project,
+            name,
+            command,
+            env,
+            remove,
+        } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let p = &mut projects[idx];
+            let Some(name) = name else {
+                let profiles = p.all_profiles();
+                if profiles.is_empty() {
+                    println!("'{}' has no profiles", p.name);
+                }
+                for profile in &profiles {
+                    let origin = if p.profiles.contains(profile) {
+                        ""
+                    } else {
+                        "  (nodash.toml)"
+                    };
+                    println!("{}\t{}{}", profile.name, profile.describe(), origin);
+                }
+                return Ok(());
+            };
+
+            if remove {
+                p.remove_profile(&name)?;
+                println!("Removed profile '{}' from '{}'", name, p.name);
+            } else if command.is_none() && env.is_empty() {
+                println!("{}", p.profile(&name)?.describe());
+                return Ok(());
+            } else {
+                let profile = Profile {
+                    name: name.clone(),
+                    command,
+                    env,
+                };
+                let summary = profile.describe();
+                let verb = if p.set_profile(profile) {
+                    "Updated"
+                } else {
+                    "Added"
+                };
+                println!(
+                    "🚀 {} profile '{}' for '{}': {}",
+                    verb, name, p.name, summary
+                );
+            }
+            save_projects(&mut projects)?;
+        }
+        Command::Tag { project, tags } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let added = projects[idx].add_tags(&tags);
+            let p = projects[idx].clone();
+            save_projects(&mut projects)?;
+            if added.is_empty() {
+                println!("'{}' already has those tags", p.name);
+            } else {
+                println!("🏷️  Tagged '{}': {}", p.name, p.tags.join(", "));
+            }
+        }
+        Command::Untag { project, tags } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let removed = projects[idx].remove_tags(&tags);
+            let p = projects[idx].clone();
+            save_projects(&mut projects)?;
+            if removed.is_empty() {
+                println!("'{}' has none of those tags", p.name);
+            } else {
+                println!("🏷️  Removed {} from '{}'", removed.join(", "), p.name);
+            }
+        }
+        Command::List {
+            query,
+            format,
+            sort,
+        } => {
+            let mut projects = load_projects()?;
+            sort_projects(&mut projects, sort);
+            let query = query.unwrap_or_default();
+            let matching: Vec<&Project> = projects
+                .iter()
+                .filter(|p| p.matches_query(&query))
+                .collect();
+            write_stdout(&render(&matching, &format)?)?;
+        }
+        Command::Open {
+            project,
+            profile,
+            package,
+            here,
+        } => {
+            let mut projects = load_projects()?;
+            let idx = find_project(&projects, &project)?;
+            let run = match profile {
+                Some(name) => Some(Run::Profile(projects[idx].profile(&name)?)),
+                None => None,
+            };
+            let dir = match package {
+                Some(name) => Some(find_package(&projects[idx], &name)?),
+                None => None,
+            };
+            if here {
+                let mut shell = shell_here(&mut projects[idx], dir.as_deref(), run.as_ref())?;
+                warn_missing_node(&projects[idx]);
+                save_projects(&mut projects)?;
+                return Err(exec_shell(&mut shell));
+            }
+            open_project(&mut projects[idx], dir.as_deref(), run.as_ref())?;
+            warn_missing_node(&projects[idx]);
+            save_projects(&mut projects)?;
+        }
+    }
+    Ok(())
+}
+
+// A workspace package by its package name or its path in the repository
+fn find_package(project: &Project, name: &str) -> io::Result<PathBuf> {
+    let packages = package::workspace_packages(&project.path);
+    if packages.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("'{}' has no workspace packages", project.name),
+        ));
+    }
+    packages
+        .into_iter()
+        .find(|p| {
+            p.name == name
+                || p.path
+                    .strip_prefix(&project.path)
+                    .is_ok_and(|rel| rel == Path::new(name))
+        })
+        .map(|p| p.path)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No package '{}' in workspace '{}'", name, project.name),
+            )
+        })
+}
+
+// Only returns when the shell could not be started
+// Output meant for other programs; a reader that quit early, like `head`,
+// is not an error
+fn write_stdout(text: &str) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match out.write_all(text.as_bytes()).and_then(|()| out.flush()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn exec_shell(shell: &mut std::process::Command) -> io::Error {
+    let error = shell.exec();
+    io::Error::new(
+        error.kind(),
+        format!(
+            "Failed to start {}: {}",
+            shell.get_program().to_string_lossy(),
+            error
+        ),
+    )
+}
+
+// The new terminal installs missing versions itself; say why it is slow
+fn warn_missing_node(project: &Project) {
+    if let (Some(spec), Some(installed)) = (&project.node_version, Installed::scan())
+        && let Resolution::Missing = installed.resolve(spec)
+        // Ranges cannot be installed, the shell says so itself
+        && node::activation(spec, Some(&installed)).is_some()
+    {
+        eprintln!(
+            "nodash: Node {} is not installed yet, nvm/fnm will install it",
+            spec
+        );
+    }
+}
+
+// Lists projects whose directory is gone and removes them, or moves them to
+// a new path while keeping their history, all in a single save. Moved git
+// repositories are looked for under the configured roots.
+fn prune(dry_run: bool, yes: bool) -> io::Result<()> {
+    let mut projects = load_projects()?;
+    let missing: Vec<usize> = (0..projects.len())
+        .filter(|&idx| projects[idx].is_missing())
+        .collect();
+    if missing.is_empty() {
+        println!("All projects exist, nothing to prune");
+        return Ok(());
+    }
+
+    let found = find_moved(&projects, &missing, &Config::load()?);
+    println!(
+        "{} project{} no longer exist{}:",
+        missing.len(),
+        if missing.len() == 1 { "" } else { "s" },
+        if missing.len() == 1 { "s" } else { "" }
+    );
+    let width = missing
+        .iter()
+        .map(|&idx| projects[idx].name.len())
+        .max()
+        .unwrap_or(0);
+    for (&idx, moved) in missing.iter().zip(&found) {
+        let p = &projects[idx];
+        println!("  - {:width$}  {}", p.name, p.path.display(), width = width);
+        if let Some(moved) = moved {
+            println!(
+                "    {:width$}  → found at {}",
+                "",
+                moved.display(),
+                width = width
+            );
+        }
+    }
+    if dry_run {
+        println!("Dry run, nothing changed");
+        return Ok(());
+    }
+
+    let mut remove = Vec::new();
+    let mut relocated = 0;
+    for (&idx, moved) in missing.iter().zip(&found) {
+        if yes {
+            match moved {
+                Some(moved) => {
+                    relocate(&mut projects, idx, moved)?;
+                    relocated += 1;
+                }
+                None => remove.push(idx),
+            }
+            continue;
+        }
+        loop {
+            match moved {
+                Some(moved) => print!(
+                    "{}: [M]ove to {}, [r]emove, [k]eep, or type its new path: ",
+                    projects[idx].name,
+                    moved.display()
+                ),
+                None => print!(
+                    "{}: [r]emove, [k]eep, or type its new path: ",
+                    projects[idx].name
+                ),
+            }
+            io::stdout().flush()?;
+            let mut answer = String::new();
+            // End of input keeps whatever is left
+            if io::stdin().read_line(&mut answer)? == 0 {
+                println!();
+                break;
+            }
+            let target = match (answer.trim(), moved) {
+                ("r" | "remove", _) => {
+                    remove.push(idx);
+                    None
+                }
+                ("" | "m" | "move", Some(moved)) => Some(moved.clone()),
+                ("" | "k" | "keep", _) => None,
+                (path, _) => Some(PathBuf::from(path)),
+            };
+            if let Some(target) = target {
+                match relocate(&mut projects, idx, &target) {
+                    Ok(()) => relocated += 1,
+                    Err(e) => {
+                        println!("  ! {}", e);
+                        continue;
+                    }
+                }
+            }
+            break;
+        }
+    }
+
+    if remove.is_empty() && relocated == 0 {
+        println!("Nothing changed");
+        return Ok(());
+    }
+    let removed = remove.len();
+    for idx in remove.into_iter().rev() {
+        projects.remove(idx);
+    }
+    save_projects(&mut projects)?;
+    println!(
+        "🧹 Removed {} and relocated {} project(s)",
+        removed, relocated
+    );
+    Ok(())
+}
+
+// New locations of the `missing` projects, matched by git identity among the
+// repositories under the configured roots. Without roots, the directories
+// around the stored projects are searched, which is where moves usually go.
+fn find_moved(projects: &[Project], missing: &[usize], config: &Config) -> Vec<Option<PathBuf>> {
+    if missing.iter().all(|&idx| projects[idx].git.is_none()) {
+        return vec![None; missing.len()];
+    }
+
+    let mut roots = config.roots.clone();
+    if roots.is_empty() {
+        roots = projects
+            .iter()
+            .filter_map(|p| p.path.ancestors().skip(1).find(|dir| dir.is_dir()))
+            .map(Path::to_path_buf)
+            .collect();
+    }
+    roots.sort();
+    roots.dedup();
+    // Nested roots would only find the same repositories again
+    let roots: Vec<&PathBuf> = roots
+        .iter()
+        .filter(|root| {
+            !roots
+                .iter()
+                .any(|other| other != *root && root.starts_with(other))
+        })
+        .collect();
+
+    let repositories: Vec<(PathBuf, GitIdentity)> = roots
+        .iter()
+        .flat_map(|root| find_repositories(root, config.depth))
+        .filter_map(|repo| GitIdentity::read(&repo).map(|identity| (repo, identity)))
+        .collect();
+
+    missing
+        .iter()
+        .map(|&idx| {
+            let identity = projects[idx].git.as_ref()?;
+            repositories
+                .iter()
+                .filter(|(_, candidate)| identity.same_repository(candidate))
+                // join("") would add a trailing slash
+                .map(|(repo, _)| {
+                    if identity.subdir.as_os_str().is_empty() {
+                        repo.clone()
+                    } else {
+                        repo.join(&identity.subdir)
+                    }
+                })
+                .find(|path| path.is_dir() && !projects.iter().any(|p| &p.path == path))
+        })
+        .collect()
+}
+
+// Points a project at its new directory; name, tags, pins and history stay
+fn relocate(projects: &mut [Project], idx: usize, path: &Path) -> io::Result<()> {
+    let path = absolute_path(path)?;
+    if !path.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", path.display()),
+        ));
+    }
+    if let Some(other) = projects.iter().find(|p| p.path == path) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is already tracked as '{}'", path.display(), other.name),
+        ));
+    }
+    let project = &mut projects[idx];
+    project.path = path;
+    // The move stands even if the manifest there needs fixing
+    if let Err(e) = project.detect_tooling() {
+        println!("  ! {}", e);
+    }
+    Ok(())
+}
+
+// Previews the projects found under `dir` and adds the new ones once
+// confirmed, all in a single save
+fn scan(dir: &Path, depth: usize, yes: bool) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    let found = find_projects(dir, depth);
+    let mut projects = load_projects()?;
+    let known = projects.len();
+
+    let mut existing = 0;
+    let mut failed = Vec::new();
+    for path in &found {
+        match add_project(&mut projects, path, None) {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => existing += 1,
+            Err(e) => failed.push((path, e)),
+        }
+    }
+    let new = &projects[known..];
+
+    println!(
+        "Found {} Node project{} under {}",
+        found.len(),
+        if found.len() == 1 { "" } else { "s" },
+        dir.display()
+    );
+    let width = new.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    for p in new {
+        let mut details = Vec::new();
+        if let Some(version) = &p.node_version {
+            details.push(format!("Node {}", version));
+        }
+        if let Some(manager) = p.package_manager {
+            details.push(manager.to_string());
+        }
+        let details = if details.is_empty() {
+            String::new()
+        } else {
+            format!("  ({})", details.join(", "))
+        };
+        println!(
+            "  + {:width$}  {}{}",
+            p.name,
+            p.path.display(),
+            details,
+            width = width
+        );
+    }
+    for (path, e) in &failed {
+        println!("  ! {}: {}", path.display(), e);
+    }
+    if existing > 0 {
+        println!("  {} already in nodash, skipped", existing);
+    }
+
+    if new.is_empty() {
+        println!("Nothing new to add");
+        return Ok(());
+    }
+    if !yes {
+        print!("Add {} project(s)? [y/N] ", new.len());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            println!("Nothing added");
+            return Ok(());
+        }
+    }
+
+    let added = new.len();
+    save_projects(&mut projects)?;
+    println!("✅ Added {} project(s) to nodash", added);
+    Ok(())
+}
+
+fn add(path: &Path, name: Option<&str>) -> io::Result<()> {
+    let mut projects = load_projects()?;
+    let project = add_project(&mut projects, path, name)?;
+    save_projects(&mut projects)?;
+
+    println!("✅ Added '{}' to nodash", project.name);
+    println!("   Path: {}", project.path.display());
+    if let Some(version) = project.node_version {
+        println!(
+            "   Node version: {} (from {})",
+            version,
+            project.node_source.unwrap_or_default()
+        );
+    } else {
+        println!("   No Node version specified");
+    }
+    if let Some(manager) = project.package_manager {
+        println!("   Package manager: {}", manager);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::store_path;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn long_flags_take_inline_or_separate_values() {
+        let parsed =
+            parse_flags("open", &args("api --profile=dev --package web"), OPEN_FLAGS).unwrap();
+        assert_eq!(parsed.positional, ["api"]);
+        assert_eq!(parsed.value("profile").as_deref(), Some("dev"));
+        assert_eq!(parsed.value("package").as_deref(), Some("web"));
+        assert!(!parsed.has("here"));
+    }
+
+    #[test]
+    fn short_flags() {
+        let Command::Pick {
+            query, select_one, ..
+        } = parse_args(&args("pick -1 -s name my api")).unwrap()
+        else {
+            panic!("expected pick");
+        };
+        assert_eq!(query.as_deref(), Some("my api"));
+        assert!(select_one);
+
+        let parsed = parse_flags("pin", &args("api -p 2"), PIN_FLAGS).unwrap();
+        assert_eq!(parsed.value("position").as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn profile_command_passes_through_after_double_dash() {
+        let Command::Profile {
+            project,
+            name,
+            command,
+            ..
+        } = parse_args(&args("profile api dev -- pnpm dev --port 3001 --help")).unwrap()
+        else {
+            panic!("expected profile");
+        };
+        assert_eq!(project, "api");
+        assert_eq!(name.as_deref(), Some("dev"));
+        assert_eq!(command.as_deref(), Some("pnpm dev --port 3001 --help"));
+    }
+
+    #[test]
+    fn store_option_is_taken_from_anywhere_before_double_dash() {
+        let rest = take_store_option(&args("list --store=/tmp/nodash-test.json api")).unwrap();
+        assert_eq!(rest, args("list api"));
+        assert_eq!(store_path().unwrap(), Path::new("/tmp/nodash-test.json"));
+
+        let rest = take_store_option(&args("profile api dev -- run --store x")).unwrap();
+        assert_eq!(rest, args("profile api dev -- run --store x"));
+    }
+
+    #[test]
+    fn usage_errors() {
+        for line in [
+            "open api --profile",
+            "list --json --format {name}",
+            "list --bogus",
+            "bogus",
+        ] {
+            let Err(err) = parse_args(&args(line)) else {
+                panic!("'{}' should be refused", line);
+            };
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::scan::DEFAULT_DEPTH;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// The user's settings from `config.toml`.
+///
+/// ```toml
+/// # Where to look for repositories that moved, see `nodash prune`
+/// roots = ["~/code", "~/work"]
+/// depth = 3
+///
+/// # Terminals to try first, ahead of the one nodash runs in
+/// terminals = ["foot", "kitty"]
+/// # Open kitty and WezTerm tabs instead of windows when possible
+/// tabs = true
+///
+/// # Inside tmux: "window", "session", or "off" for terminals as usual
+/// [tmux]
+/// mode = "window"
+/// reuse = true
+///
+/// # Inside Zellij: "tab", "pane", or "off" for terminals as usual
+/// [zellij]
+/// mode = "tab"
+/// layouts = true
+///
+/// [terminal.foot]
+/// command = ["foot", "--title", "{title}", "--working-directory", "{cwd}", "{shell}"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Directories searched for moved projects; `~/` is expanded.
+    pub roots: Vec<PathBuf>,
+    /// How many levels below each root to search.
+    pub depth: usize,
+    /// Preferred terminals by name, best first.
+    pub terminals: Vec<String>,
+    /// User-defined terminals, see `terminal::Template`.
+    pub terminal: BTreeMap<String, TerminalTemplate>,
+    /// New tabs in the running kitty or WezTerm rather than new windows.
+    pub tabs: bool,
+    pub tmux: TmuxConfig,
+    pub zellij: ZellijConfig,
+}
+
+/// How projects open when nodash runs inside tmux.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TmuxConfig {
+    pub mode: TmuxMode,
+    /// Switch to the project's window or session when there is one.
+    pub reuse: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TmuxMode {
+    /// A new window in the current session.
+    #[default]
+    Window,
+    /// A session per project, switched to.
+    Session,
+    /// Ignore tmux and open a terminal.
+    Off,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerminalTemplate {
+    /// Program and arguments, with `{shell}`, `{cwd}` and `{title}`.
+    pub command: Vec<String>,
+}
+
+/// How projects open when nodash runs inside Zellij.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZellijConfig {
+    pub mode: ZellijMode,
+    /// Open new tabs with the repository's own layout file when it has one
+    /// and nothing is to be run.
+    pub layouts: bool,
+}
+
+impl Default for ZellijConfig {
+    fn default() -> ZellijConfig {
+        ZellijConfig {
+            mode: ZellijMode::Tab,
+            layouts: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZellijMode {
+    /// A new tab named after the project.
+    #[default]
+    Tab,
+    /// A new pane in the current tab.
+    Pane,
+    /// Ignore Zellij and open a terminal.
+    Off,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            roots: Vec::new(),
+            depth: DEFAULT_DEPTH,
+            terminals: Vec::new(),
+            terminal: BTreeMap::new(),
+            tabs: true,
+            tmux: TmuxConfig::default(),
+            zellij: ZellijConfig::default(),
+        }
+    }
+}
+
+/// Where `config.toml` lives: `NODASH_HOME` when set, like the store,
+/// otherwise `$XDG_CONFIG_HOME/nodash` (usually `~/.config/nodash`).
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(home) = env::var_os("NODASH_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(home).join(CONFIG_FILE));
+    }
+    dirs::config_dir().map(|dir| dir.join("nodash").join(CONFIG_FILE))
+}
+
+impl Config {
+    /// Reads `config.toml`; a missing file gives the defaults, a broken one
+    /// an error naming the file.
+    pub fn load() -> io::Result<Config> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e),
+        };
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        };
+        let mut config: Config =
+            toml::from_str(&content).map_err(|e| invalid(e.to_string().trim_end().to_string()))?;
+        if let Some(name) = config
+            .terminal
+            .iter()
+            .find_map(|(name, t)| t.command.is_empty().then_some(name))
+        {
+            return Err(invalid(format!("terminal '{}' has an empty command", name)));
+        }
+
+        if let Some(home) = dirs::home_dir() {
+            for root in &mut config.roots {
+                if let Ok(rest) = root.strip_prefix("~") {
+                    *root = home.join(rest);
+                }
+            }
+        }
+        Ok(config)
+    }
+}
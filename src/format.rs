@@ -0,0 +1,248 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::io;
+
+use crate::node::{self, Installed, Resolution};
+use crate::project::Project;
+
+pub enum ListFormat {
+    Tsv,
+    Json,
+    /// A user supplied line template such as `{name}\t{path}`.
+    Template(String),
+}
+
+impl ListFormat {
+    pub fn parse(value: &str) -> ListFormat {
+        match value {
+            "tsv" => ListFormat::Tsv,
+            "json" => ListFormat::Json,
+            template => ListFormat::Template(template.to_string()),
+        }
+    }
+}
+
+/// A project record together with fields derived at listing time.
+#[derive(Serialize)]
+struct ListEntry<'a> {
+    #[serde(flatten)]
+    project: &'a Project,
+    exists: bool,
+    frecency: f64,
+    /// Installed version satisfying `node_version`, if any.
+    node_resolved: Option<String>,
+    /// What nvm or fnm are asked to activate, see `node::activation`.
+    node_use: Option<String>,
+}
+
+impl<'a> ListEntry<'a> {
+    fn new(project: &'a Project, installed: Option<&Installed>) -> Self {
+        let node_resolved = match (installed, &project.node_version) {
+            (Some(installed), Some(spec)) => match installed.resolve(spec) {
+                Resolution::Installed(v) => Some(v.to_string()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let node_use = project
+            .node_version
+            .as_deref()
+            .and_then(|spec| node::activation(spec, installed));
+        ListEntry {
+            project,
+            exists: !project.is_missing(),
+            frecency: project.frecency(Utc::now()),
+            node_resolved,
+            node_use,
+        }
+    }
+
+    /// Value of a named field as plain text, `None` for unknown fields.
+    fn field(&self, name: &str) -> Option<String> {
+        let p = self.project;
+        let value = match name {
+            "name" => p.name.clone(),
+            "path" => p.path.display().to_string(),
+            "node_version" => p.node_version.clone().unwrap_or_default(),
+            "node_source" => p.node_source.clone().unwrap_or_default(),
+            "node_resolved" => self.node_resolved.clone().unwrap_or_default(),
+            "node_use" => self.node_use.clone().unwrap_or_default(),
+            "profiles" => p
+                .all_profiles()
+                .iter()
+                .map(|profile| profile.name.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            "package_manager" => p.package_manager.map(|m| m.to_string()).unwrap_or_default(),
+            "last_opened" => p.last_opened.map(|ts| ts.to_rfc3339()).unwrap_or_default(),
+            "tags" => p.all_tags().join(","),
+            "frecency" => format!("{:.2}", self.frecency),
+            "pinned" => p.pinned.map(|pos| pos.to_string()).unwrap_or_default(),
+            "exists" => self.exists.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+const TSV_FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "node_version",
+    "last_opened",
+    "exists",
+    "tags",
+    "frecency",
+    "pinned",
+    "node_source",
+    "node_resolved",
+    "package_manager",
+    "profiles",
+    "node_use",
+];
+
+pub fn render(projects: &[&Project], format: &ListFormat) -> io::Result<String> {
+    let installed = Installed::scan();
+    let entries: Vec<ListEntry> = projects
+        .iter()
+        .map(|p| ListEntry::new(p, installed.as_ref()))
+        .collect();
+
+    let out = match format {
+        ListFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&entries)?;
+            json.push('\n');
+            json
+        }
+        ListFormat::Tsv => entries
+            .iter()
+            .map(|e| {
+                let fields: Vec<String> = TSV_FIELDS
+                    .iter()
+                    .map(|f| e.field(f).unwrap_or_default().replace(['\t', '\n'], " "))
+                    .collect();
+                fields.join("\t") + "\n"
+            })
+            .collect(),
+        ListFormat::Template(template) => {
+            let mut out = String::new();
+            for e in &entries {
+                out.push_str(&expand_template(template, e)?);
+                out.push('\n');
+            }
+            out
+        }
+    };
+    Ok(out)
+}
+
+/// Renders a single project; JSON output is an object rather than an array.
+pub fn render_one(project: &Project, format: &ListFormat) -> io::Result<String> {
+    match format {
+        ListFormat::Json => {
+            let mut json =
+                serde_json::to_string_pretty(&ListEntry::new(project, Installed::scan().as_ref()))?;
+            json.push('\n');
+            Ok(json)
+        }
+        _ => render(&[project], format),
+    }
+}
+
+// Replaces `{field}` placeholders and the `\t` / `\n` escapes shells make
+// awkward to type. `{{` and `}}` produce literal braces.
+fn expand_template(template: &str, entry: &ListEntry) -> io::Result<String> {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                // An unclosed `{name` is as unknown as a misspelled one
+                let value = entry.field(&name).filter(|_| closed).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Unknown template field '{{{}{}', expected one of: {}",
+                            name,
+                            if closed { "}" } else { "" },
+                            TSV_FIELDS.join(", ")
+                        ),
+                    )
+                })?;
+                out.push_str(&value);
+            }
+            '\\' => match chars.peek() {
+                Some('t') => {
+                    chars.next();
+                    out.push('\t');
+                }
+                Some('n') => {
+                    chars.next();
+                    out.push('\n');
+                }
+                _ => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn expand(template: &str) -> io::Result<String> {
+        let project: Project = serde_json::from_value(json!({
+            "name": "api",
+            "path": "/code/api",
+            "node_version": "20",
+            "tags": ["backend", "work"],
+        }))
+        .unwrap();
+        expand_template(template, &ListEntry::new(&project, None))
+    }
+
+    #[test]
+    fn fields_and_escapes() {
+        assert_eq!(expand("{name}\\t{path}").unwrap(), "api\t/code/api");
+        assert_eq!(expand("{{{name}}}").unwrap(), "{api}");
+        assert_eq!(expand("a\\b").unwrap(), "a\\b");
+        assert_eq!(
+            expand("{node_version}\\n{tags}").unwrap(),
+            "20\nbackend,work"
+        );
+        // Without installed versions, nothing resolves
+        assert_eq!(expand("[{node_resolved}]").unwrap(), "[]");
+    }
+
+    #[test]
+    fn unknown_fields_are_usage_errors() {
+        let err = expand("{nmae}").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("'{nmae}'"));
+
+        let err = expand("{name").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("'{name'"));
+    }
+}
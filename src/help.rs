@@ -1,22 +1,334 @@
+use std::io;
+
 use crate::version::VERSION;
 
 pub fn show_help() {
     println!("nodash {} - Node.js project dashboard", VERSION);
     println!();
     println!("USAGE:");
-    println!("    nodash [COMMAND]");
+    println!("    nodash [COMMAND] [OPTIONS]");
     println!();
     println!("COMMANDS:");
     println!("    help      Show this help message");
-    println!("    add       Add current directory as a project");
+    println!("    add       Add a directory as a project");
+    println!("    scan      Find Node projects under a directory and add them");
+    println!("    remove    Remove a project from nodash");
+    println!("    prune     Remove or relocate projects whose directory is gone");
+    println!("    trust     Allow a project's nodash.toml to run its commands");
+    println!("    rename    Rename a project");
+    println!("    pin       Pin a project to the top of the list");
+    println!("    unpin     Unpin a project");
+    println!("    tag       Add tags to a project");
+    println!("    untag     Remove tags from a project");
+    println!("    profile   Manage launch profiles of a project");
+    println!("    list      List stored projects");
+    println!("    open      Open a project in a new terminal");
+    println!("    pick      Choose a project in the dashboard and print its path");
+    println!("    which     Print the project containing a directory");
+    println!("    init      Print shell integration for bash, zsh or fish");
     println!("    update    Update nodash to the latest version");
     println!("    version   Show the current version of nodash");
     println!();
+    println!("GLOBAL OPTIONS:");
+    println!("    --store PATH    Use PATH as the project list instead of the default store");
+    println!();
+    println!("ENVIRONMENT:");
+    println!("    NODASH_HOME     Directory for nodash data (default: $XDG_DATA_HOME/nodash)");
+    println!("                    and config.toml (default: $XDG_CONFIG_HOME/nodash)");
+    println!();
+    println!("Run 'nodash help <COMMAND>' or 'nodash <COMMAND> --help' for details.");
+    println!();
     println!("INTERACTIVE CONTROLS:");
     println!("    ↑/↓       Navigate projects");
-    println!("    Enter     Open selected project");
+    println!("    Enter     Open selected project or workspace package");
+    println!("    h         Open in this terminal, replacing nodash");
+    println!("    →/←       Show / hide the packages of a workspace");
     println!("    a         Add new project");
-    println!("    /         Search projects");
-    println!("    Esc       Clear search");
+    println!("    p         Pin or unpin selected project");
+    println!("    [ / ]     Move pinned project up / down");
+    println!("    t         Edit tags of selected project");
+    println!("    s         Show profiles and scripts of a project or package; Enter");
+    println!("              runs one in a new terminal, h in this one");
+    println!("    g         Toggle grouping by tag (Enter folds a group)");
+    println!("    o         Cycle sort order: frecency, recent, name");
+    println!("    /         Search projects (tag:NAME filters by tag)");
+    println!("    Esc       Clear search, close the scripts panel");
     println!("    q         Quit");
-}
\ No newline at end of file
+}
+
+/// Prints the usage of a single subcommand, falling back to the general help
+/// for commands that have nothing more to say.
+pub fn show_command_help(command: &str) -> io::Result<()> {
+    match command {
+        "add" => {
+            println!("Add a directory as a project");
+            println!();
+            println!("USAGE:");
+            println!("    nodash add [PATH] [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!(
+                "    PATH               Project directory (defaults to the current directory)"
+            );
+            println!();
+            println!("OPTIONS:");
+            println!(
+                "    -n, --name NAME    Name to show in nodash (defaults to the directory name)"
+            );
+        }
+        "scan" => {
+            println!("Find Node projects under a directory and add the new ones");
+            println!();
+            println!("USAGE:");
+            println!("    nodash scan [DIR] [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!(
+                "    DIR                Directory to search (defaults to the current directory)"
+            );
+            println!();
+            println!("OPTIONS:");
+            println!("    -d, --depth N      How many levels below DIR to search (default: 3)");
+            println!("    -y, --yes          Add without asking for confirmation");
+            println!();
+            println!("A directory with a package.json is a project; its subdirectories, hidden");
+            println!("directories and node_modules are not searched. Projects already in nodash");
+            println!("are skipped.");
+        }
+        "prune" => {
+            println!("Remove or relocate projects whose directory no longer exists");
+            println!();
+            println!("USAGE:");
+            println!("    nodash prune [OPTIONS]");
+            println!();
+            println!("OPTIONS:");
+            println!("    -n, --dry-run      Only list the missing projects");
+            println!(
+                "    -y, --yes          Relocate the ones found elsewhere and remove the rest"
+            );
+            println!("                       without asking");
+            println!();
+            println!("Git repositories that moved are recognised by their remote and first");
+            println!("commit under the 'roots' of config.toml, or next to the stored projects.");
+            println!("Otherwise each missing project is removed, kept, or moved to a new path");
+            println!("you type; a moved project keeps its name, tags, pin and history.");
+        }
+        "remove" | "rm" => {
+            println!("Remove a project from nodash (the directory itself is left untouched)");
+            println!();
+            println!("USAGE:");
+            println!("    nodash remove <PROJECT>");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT            Project name or path");
+        }
+        "trust" => {
+            println!("Allow a project's nodash.toml to export its env files and run its");
+            println!("pre_open commands when the project is opened");
+            println!();
+            println!("USAGE:");
+            println!("    nodash trust <PROJECT>");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT            Project name or path");
+            println!();
+            println!("Until then they are skipped. Trust covers the manifest as it is now; after");
+            println!("any change to it, run 'nodash trust' again.");
+        }
+        "rename" | "mv" => {
+            println!("Rename a project");
+            println!();
+            println!("USAGE:");
+            println!("    nodash rename <PROJECT> <NEW_NAME>");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT            Project name or path");
+            println!("    NEW_NAME           New name to show in nodash");
+        }
+        "pin" | "unpin" => {
+            println!("Pin projects so they stay at the top of the list in a fixed order");
+            println!();
+            println!("USAGE:");
+            println!("    nodash pin <PROJECT> [OPTIONS]");
+            println!("    nodash unpin <PROJECT>");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT              Project name or path");
+            println!();
+            println!("OPTIONS:");
+            println!("    -p, --position N     Pin position, 1 being the top (default: last)");
+        }
+        "tag" | "untag" => {
+            println!("Add or remove project tags");
+            println!();
+            println!("USAGE:");
+            println!("    nodash tag <PROJECT> <TAG>...");
+            println!("    nodash untag <PROJECT> <TAG>...");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT            Project name or path");
+            println!("    TAG                Tag to add or remove");
+            println!();
+            println!(
+                "Filter by tag with 'nodash list tag:NAME' or 'tag:NAME' in the dashboard search."
+            );
+        }
+        "list" | "ls" => {
+            println!("List stored projects");
+            println!();
+            println!("USAGE:");
+            println!("    nodash list [QUERY] [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!(
+                "    QUERY                Only list projects whose name or path contains QUERY"
+            );
+            println!();
+            println!("OPTIONS:");
+            println!("    -s, --sort MODE      frecency (default), recent or name");
+            println!("        --json           Print projects as a JSON array");
+            println!("    -f, --format FORMAT  tsv (default), json, or a template such as");
+            println!("                         '{{name}}\\t{{path}}'");
+            println!();
+            println!("FIELDS:");
+            println!("    name, path, node_version, last_opened, exists, tags, frecency, pinned,");
+            println!("    node_source, node_resolved, package_manager, profiles, node_use");
+        }
+        "open" => {
+            println!("Open a project in a new terminal, activating its Node.js version");
+            println!();
+            println!("USAGE:");
+            println!("    nodash open <PROJECT> [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT            Project name or path");
+            println!();
+            println!("OPTIONS:");
+            println!("    -p, --profile NAME   Start the launch profile NAME once Node is active");
+            println!("        --package NAME   Open the workspace package NAME (package name or");
+            println!("                         path in the repository) instead of the root");
+            println!(
+                "        --here           Start the shell in this terminal, replacing nodash,"
+            );
+            println!("                         e.g. over SSH or without a graphical terminal");
+            println!();
+            println!("A nodash.toml in the project root adds env files, pre-open commands,");
+            println!("tags and profiles shared by the team.");
+            println!();
+            println!("The terminal is the first installed one of: 'terminals' in config.toml,");
+            println!("the terminal nodash runs in, xterm, gnome-terminal, konsole. config.toml");
+            println!(
+                "can define terminals as command templates using {{shell}}, {{cwd}} and {{title}}."
+            );
+            println!("Inside kitty or WezTerm the project opens as a new tab when remote control");
+            println!("allows it. Inside tmux it opens as a tmux window, or a session of its own");
+            println!("with mode = \"session\" in the [tmux] section of config.toml. Inside Zellij");
+            println!("it opens as a new tab, using the repository's .zellij/layout.kdl or");
+            println!("zellij.kdl when there is one, unless a profile or script is to be run.");
+        }
+        "profile" => {
+            println!("Manage launch profiles: a command and variables to start a project with");
+            println!();
+            println!("USAGE:");
+            println!("    nodash profile <PROJECT> <NAME> [-e KEY=VALUE]... [--] [COMMAND]...");
+            println!("    nodash profile <PROJECT> [NAME]");
+            println!("    nodash profile <PROJECT> <NAME> --remove");
+            println!();
+            println!("ARGS:");
+            println!("    PROJECT              Project name or path");
+            println!("    NAME                 Profile name; lists all profiles when omitted");
+            println!("    COMMAND              Command run by your shell once Node is active");
+            println!();
+            println!("OPTIONS:");
+            println!("    -e, --env KEY=VALUE  Export a variable first; may be repeated");
+            println!("        --remove         Delete the profile");
+            println!();
+            println!("Put the command after '--' when it has options of its own, e.g.");
+            println!("    nodash profile api test -- vitest --watch");
+        }
+        "pick" => {
+            println!("Choose a project in the dashboard and print its path instead of opening it");
+            println!();
+            println!("USAGE:");
+            println!("    nodash pick [QUERY]... [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!("    QUERY                Start with the search pre-filled");
+            println!();
+            println!("OPTIONS:");
+            println!(
+                "    -1, --select-1       Skip the dashboard when QUERY matches a single project"
+            );
+            println!("        --json           Print the picked project as a JSON object");
+            println!("    -f, --format FORMAT  Output template, '{{path}}' by default");
+            println!();
+            println!(
+                "The dashboard is drawn on stderr, so this works inside command substitution:"
+            );
+            println!("    cd \"$(nodash pick)\"");
+            println!();
+            println!("Exits with a non-zero status when the dashboard is closed without picking.");
+        }
+        "which" => {
+            println!("Print the project containing a directory, deepest match first");
+            println!();
+            println!("USAGE:");
+            println!("    nodash which [PATH] [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!(
+                "    PATH                 Directory to look up (defaults to the current directory)"
+            );
+            println!();
+            println!("OPTIONS:");
+            println!("        --json           Print the project as a JSON object");
+            println!("    -f, --format FORMAT  Output template, '{{path}}' by default");
+            println!();
+            println!("Exits with a non-zero status when PATH is not inside any project.");
+        }
+        "init" => {
+            println!("Print shell integration that jumps to projects in the current shell");
+            println!();
+            println!("USAGE:");
+            println!("    nodash init <SHELL> [OPTIONS]");
+            println!();
+            println!("ARGS:");
+            println!("    SHELL                bash, zsh or fish");
+            println!();
+            println!("OPTIONS:");
+            println!("        --cmd NAME       Name of the jump function (default: nd)");
+            println!(
+                "        --hook           Also activate the project's Node.js version on every cd"
+            );
+            println!();
+            println!("SETUP:");
+            println!("    bash   eval \"$(nodash init bash)\"        in ~/.bashrc");
+            println!("    zsh    eval \"$(nodash init zsh)\"         in ~/.zshrc");
+            println!("    fish   nodash init fish | source          in ~/.config/fish/config.fish");
+            println!();
+            println!(
+                "Then 'nd [QUERY]' picks a project, cds into it and runs nvm/fnm use in place."
+            );
+        }
+        "update" => {
+            println!("Update nodash to the latest version");
+            println!();
+            println!("USAGE:");
+            println!("    nodash update");
+        }
+        "version" | "--version" | "-v" => {
+            println!("Show the current version of nodash");
+            println!();
+            println!("USAGE:");
+            println!("    nodash version");
+        }
+        "help" | "--help" | "-h" => show_help(),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown command: {}", command),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::io;
+
+use crate::shell::{FISH_USE_NODE, POSIX_LOAD_NVM, POSIX_USE_NODE};
+
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+fn indent(script: &str, width: usize) -> String {
+    let pad = " ".repeat(width);
+    script
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{}{}\n", pad, line)
+            }
+        })
+        .collect()
+}
+
+/// Builds the integration script for `shell`, meant to be evaluated by the
+/// user's rc file. `cmd` names the jump function; `hook` adds a directory
+/// change hook that activates Node whenever the shell enters a project.
+pub fn init_script(shell: &str, cmd: &str, hook: bool) -> io::Result<String> {
+    let script = match shell {
+        "bash" | "zsh" => posix_script(shell, cmd, hook),
+        "fish" => fish_script(cmd, hook),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unsupported shell '{}', expected one of: {}",
+                    shell,
+                    SHELLS.join(", ")
+                ),
+            ));
+        }
+    };
+    Ok(script)
+}
+
+fn posix_script(shell: &str, cmd: &str, hook: bool) -> String {
+    let mut script = format!(
+        r#"# nodash {shell} integration
+# Add to your ~/.{shell}rc:  eval "$(nodash init {shell})"
+
+# Activates the version the current project asks for, if any
+_nodash_use_node() {{
+  local _nodash_node
+  _nodash_node="$(command nodash which -f '{{node_use}}' 2>/dev/null)"
+  [ -n "$_nodash_node" ] || return 0
+  if ! command -v nvm >/dev/null 2>&1; then
+{load}  fi
+{use_node}  hash -r
+}}
+
+{cmd}() {{
+  local dir
+  dir="$(command nodash pick --select-1 -- "$@")" || return
+  cd -- "$dir" || return
+  _NODASH_PROJECT="$dir"
+  _nodash_use_node
+}}
+"#,
+        load = indent(POSIX_LOAD_NVM, 4),
+        use_node = indent(POSIX_USE_NODE, 2),
+    );
+
+    if hook {
+        script.push_str(
+            r#"
+_nodash_hook() {
+  local project
+  project="$(command nodash which 2>/dev/null)" || {
+    _NODASH_PROJECT=
+    return
+  }
+  [ "$project" = "$_NODASH_PROJECT" ] && return
+  _NODASH_PROJECT="$project"
+  _nodash_use_node
+}
+"#,
+        );
+        if shell == "zsh" {
+            script.push_str(
+                r#"
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _nodash_hook
+_nodash_hook
+"#,
+            );
+        } else {
+            script.push_str(
+                r#"
+_nodash_prompt_hook() {
+  [ "$PWD" = "$_NODASH_PWD" ] && return
+  _NODASH_PWD="$PWD"
+  _nodash_hook
+}
+case ";${PROMPT_COMMAND[*]:-};" in
+  *";_nodash_prompt_hook;"*) ;;
+  *) PROMPT_COMMAND="_nodash_prompt_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+"#,
+            );
+        }
+    }
+
+    script
+}
+
+fn fish_script(cmd: &str, hook: bool) -> String {
+    let mut script = format!(
+        r#"# nodash fish integration
+# Add to your ~/.config/fish/config.fish:  nodash init fish | source
+
+# Activates the version the current project asks for, if any
+function __nodash_use_node
+    set -l _nodash_node (command nodash which -f '{{node_use}}' 2>/dev/null)
+    test -n "$_nodash_node"; or return 0
+{use_node}end
+
+function {cmd}
+    set -l dir (command nodash pick --select-1 -- $argv); or return
+    cd $dir; or return
+    set -g __nodash_project $dir
+    __nodash_use_node
+end
+"#,
+        use_node = indent(FISH_USE_NODE, 4),
+    );
+
+    if hook {
+        script.push_str(
+            r#"
+function __nodash_hook --on-variable PWD
+    set -l project (command nodash which 2>/dev/null); or begin
+        set -g __nodash_project
+        return
+    end
+    test "$project" = "$__nodash_project"; and return
+    set -g __nodash_project $project
+    __nodash_use_node
+end
+__nodash_hook
+"#,
+        );
+    }
+
+    script
+}
@@ -1,48 +1,40 @@
+mod cli;
+mod config;
+mod format;
+mod git;
 mod help;
+mod init;
+mod manifest;
+mod node;
+mod package;
 mod project;
+mod scan;
+mod schema;
 mod shell;
+mod store;
+mod terminal;
+mod tmux;
 mod tui;
 mod updater;
 mod version;
+mod zellij;
 
-use crate::help::show_help;
-use crate::project::{add_current_directory, load_projects, save_projects};
-use crate::shell::open_project;
-use crate::tui::run_app;
-use crate::updater::check_for_update;
 use std::io;
+use std::process::ExitCode;
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
 
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "help" | "--help" | "-h" => {
-                show_help();
-                return Ok(());
-            }
-            "add" => {
-                add_current_directory()?;
-                return Ok(());
-            }
-            "update" => {
-                check_for_update()?;
-                return Ok(());
-            }
-            "version" | "--version" | "-v" => {
-                println!("nodash version: {}", version::VERSION);
-                return Ok(());
-            }
-            _ => {
-                println!("Unknown command: {}", args[1]);
-                println!("Use 'nodash help' for available commands");
-                return Ok(());
-            }
+    match cli::parse_args(&args).and_then(cli::run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+            eprintln!("Error: {}", e);
+            eprintln!("Use 'nodash help' for available commands");
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
         }
     }
-
-    let mut projects = load_projects()?;
-    run_app(&mut projects, open_project)?;
-    save_projects(&projects)?;
-    Ok(())
 }
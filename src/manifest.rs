@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+
+use crate::shell::is_env_name;
+
+pub const MANIFEST_FILE: &str = "nodash.toml";
+
+/// Team-wide settings committed as `nodash.toml` in a project root.
+///
+/// ```toml
+/// name = "api"
+/// node = "20"
+/// tags = ["backend"]
+/// env_files = [".env", ".env.local"]
+/// pre_open = ["pnpm install --frozen-lockfile"]
+///
+/// [profiles.dev]
+/// command = "pnpm dev"
+/// env = { PORT = "3001" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// Default project name when adding it.
+    pub name: Option<String>,
+    /// Node version, ahead of every other source; see `node::resolve`.
+    pub node: Option<String>,
+    /// Added to the user's own tags.
+    pub tags: Vec<String>,
+    /// Dotenv files inside the project, relative to its root, exported in
+    /// order.
+    pub env_files: Vec<String>,
+    /// Commands run in the new shell once Node is active, before anything
+    /// else.
+    pub pre_open: Vec<String>,
+    /// Launch profiles; the user's own profiles of the same name win.
+    pub profiles: BTreeMap<String, ProfileSpec>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSpec {
+    pub command: Option<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+fn invalid(path: &Path, message: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), message),
+    )
+}
+
+impl Manifest {
+    /// Reads `nodash.toml` from the project root. A missing file is `None`,
+    /// a broken one an error naming the file.
+    pub fn load(project: &Path) -> io::Result<Option<Manifest>> {
+        let path = project.join(MANIFEST_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let manifest: Manifest = toml::from_str(&content)
+            .map_err(|e| invalid(&path, e.to_string().trim_end().to_string()))?;
+
+        let inside = |file: &str| {
+            Path::new(file)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        };
+        if let Some(file) = manifest.env_files.iter().find(|file| !inside(file)) {
+            return Err(invalid(
+                &path,
+                format!("env file '{}' is outside the project", file),
+            ));
+        }
+        for (name, profile) in &manifest.profiles {
+            if let Some(key) = profile.env.keys().find(|key| !is_env_name(key)) {
+                return Err(invalid(
+                    &path,
+                    format!("profile '{}' sets invalid variable '{}'", name, key),
+                ));
+            }
+        }
+        Ok(Some(manifest))
+    }
+}
+
+/// Reads a dotenv file: `KEY=VALUE` lines with optional `export`, `#`
+/// comments, and single or double quoted values. Lines that are not
+/// assignments are ignored, like most dotenv loaders do.
+pub fn read_env_file(path: &Path) -> io::Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path)?;
+    let mut vars = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_env_name(key) {
+            continue;
+        }
+
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('\'') {
+            quoted.split('\'').next().unwrap_or_default().to_string()
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            unescape_double_quoted(quoted)
+        } else {
+            // Unquoted values end at a comment
+            match value.find(" #") {
+                Some(end) => value[..end].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+// Up to the closing quote, with \n, \" and \\ escapes
+fn unescape_double_quoted(quoted: &str) -> String {
+    let mut out = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some(c @ ('"' | '\\')) => out.push(c),
+                Some(other) => {
+                    out.push('\\');
+                    out.push(other);
+                }
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
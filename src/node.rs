@@ -0,0 +1,559 @@
+use serde_json::Value;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::project::repo_ancestors;
+
+/// A Node.js version requirement together with where it was found.
+pub struct NodeVersion {
+    pub version: String,
+    /// Human readable origin, e.g. `.nvmrc` or `../package.json engines`.
+    pub source: String,
+}
+
+type Reader = fn(&str) -> Option<String>;
+
+// Version files, in precedence order within a single directory, with the
+// suffix naming the field that was read
+const SOURCES: &[(&str, &str, Reader)] = &[
+    (MANIFEST_FILE, "", read_manifest),
+    (".nvmrc", "", read_plain),
+    (".node-version", "", read_plain),
+    (".tool-versions", "", read_tool_versions),
+    ("package.json", " volta", read_volta),
+    ("package.json", " engines", read_engines),
+];
+
+/// Finds the Node.js version a project asks for.
+///
+/// Starting at `path` and walking up through its parents, each directory is
+/// checked for `nodash.toml` `node`, `.nvmrc`, `.node-version`,
+/// `.tool-versions` (asdf/mise), `package.json` `volta.node` and
+/// `package.json` `engines.node`, in that order. The nearest directory wins.
+/// The walk stops after the repository root (the first directory holding
+/// `.git`), so settings from unrelated parent directories are never picked
+/// up inside a repository.
+pub fn resolve(path: &Path) -> Option<NodeVersion> {
+    for (depth, dir) in repo_ancestors(path).enumerate() {
+        let up: PathBuf = iter::repeat_n("..", depth).collect();
+        for (file, field, read) in SOURCES {
+            let Ok(content) = fs::read_to_string(dir.join(file)) else {
+                continue;
+            };
+            if let Some(version) = read(&content) {
+                return Some(NodeVersion {
+                    version,
+                    source: format!("{}{}", up.join(file).display(), field),
+                });
+            }
+        }
+    }
+    None
+}
+
+fn clean(version: &str) -> Option<String> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+// The team's choice wins over files written for a specific version manager
+fn read_manifest(content: &str) -> Option<String> {
+    toml::from_str::<Manifest>(content)
+        .ok()?
+        .node
+        .as_deref()
+        .and_then(clean)
+}
+
+// First meaningful line; nvm allows trailing comments
+fn read_plain(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .find(|line| !line.trim().is_empty())
+        .and_then(clean)
+}
+
+// `nodejs 20.11.1` (asdf) or `node 20.11.1` (mise); the first listed
+// version is the active one
+fn read_tool_versions(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("nodejs" | "node") => words.next().and_then(clean),
+            _ => None,
+        }
+    })
+}
+
+fn package_field(content: &str, section: &str) -> Option<String> {
+    let json: Value = serde_json::from_str(content).ok()?;
+    json.get(section)?.get("node")?.as_str().and_then(clean)
+}
+
+fn read_volta(content: &str) -> Option<String> {
+    package_field(content, "volta")
+}
+
+fn read_engines(content: &str) -> Option<String> {
+    package_field(content, "engines")
+}
+
+/// A concrete Node.js release such as `20.11.1`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(u64, u64, u64);
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+impl Version {
+    fn parse(s: &str) -> Option<Version> {
+        let s = s.trim();
+        let mut parts = s.strip_prefix('v').unwrap_or(s).split('.');
+        let version = Version(
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+            parts.next()?.parse().ok()?,
+        );
+        parts.next().is_none().then_some(version)
+    }
+}
+
+/// What a version spec means on this machine.
+pub enum Resolution {
+    Installed(Version),
+    /// The spec is understood, but no installed version satisfies it.
+    Missing,
+    /// The spec could not be interpreted, e.g. `system` or a custom alias.
+    Unknown,
+}
+
+// LTS codenames, as used by `lts/<name>`
+const LTS_CODENAMES: &[(&str, u64)] = &[
+    ("argon", 4),
+    ("boron", 6),
+    ("carbon", 8),
+    ("dubnium", 10),
+    ("erbium", 12),
+    ("fermium", 14),
+    ("gallium", 16),
+    ("hydrogen", 18),
+    ("iron", 20),
+    ("jod", 22),
+    ("krypton", 24),
+];
+
+// Guards against alias files that point at each other
+const ALIAS_DEPTH: usize = 8;
+
+/// Node.js versions installed through nvm or fnm.
+pub struct Installed {
+    /// Newest first.
+    versions: Vec<Version>,
+    /// nvm's `alias` directories, for `lts/*` and user defined aliases.
+    nvm_aliases: Vec<PathBuf>,
+    /// fnm's `aliases` directories, holding symlinks to installations.
+    fnm_aliases: Vec<PathBuf>,
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+// Versions are the `vX.Y.Z` directory names under `dir`
+fn versions_in(dir: &Path) -> Vec<Version> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Version::parse(&entry.file_name().to_string_lossy()))
+        .collect()
+}
+
+impl Installed {
+    /// Looks through the nvm and fnm install directories. Returns `None` when
+    /// neither manager is set up, as nothing can be said about what is missing.
+    pub fn scan() -> Option<Installed> {
+        let home = dirs::home_dir();
+        let nvm_dirs = [
+            env_dir("NVM_DIR"),
+            home.as_ref().map(|h| h.join(".nvm")),
+            dirs::config_dir().map(|c| c.join("nvm")),
+        ];
+        let fnm_dirs = [
+            env_dir("FNM_DIR"),
+            dirs::data_dir().map(|d| d.join("fnm")),
+            home.as_ref().map(|h| h.join(".fnm")),
+        ];
+
+        let mut installed = Installed {
+            versions: Vec::new(),
+            nvm_aliases: Vec::new(),
+            fnm_aliases: Vec::new(),
+        };
+        let mut found = false;
+        for dir in nvm_dirs.into_iter().flatten().filter(|d| d.is_dir()) {
+            found = true;
+            installed
+                .versions
+                .extend(versions_in(&dir.join("versions").join("node")));
+            installed.nvm_aliases.push(dir.join("alias"));
+        }
+        for dir in fnm_dirs.into_iter().flatten().filter(|d| d.is_dir()) {
+            found = true;
+            installed
+                .versions
+                .extend(versions_in(&dir.join("node-versions")));
+            installed.fnm_aliases.push(dir.join("aliases"));
+        }
+        if !found {
+            return None;
+        }
+
+        installed.versions.sort_unstable_by(|a, b| b.cmp(a));
+        installed.versions.dedup();
+        Some(installed)
+    }
+
+    /// Resolves a spec as written in a version file: a version or range
+    /// (`20`, `20.11.1`, `>=18`, `^20.10`), `node`, `lts/*`, `lts/iron`, or
+    /// an nvm/fnm alias.
+    pub fn resolve(&self, spec: &str) -> Resolution {
+        self.resolve_within(spec, ALIAS_DEPTH)
+    }
+
+    fn resolve_within(&self, spec: &str, depth: usize) -> Resolution {
+        let spec = spec.trim();
+        match spec {
+            "system" | "iojs" => return Resolution::Unknown,
+            "node" | "stable" | "latest" | "current" => return self.newest(|_| true),
+            _ => {}
+        }
+
+        if let Some(name) = spec.strip_prefix("lts/") {
+            if let Some(resolution) = self.alias(spec, depth) {
+                return resolution;
+            }
+            if name == "*" {
+                return self.newest(|v| v.0 >= 4 && v.0 % 2 == 0);
+            }
+            return match LTS_CODENAMES
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            {
+                Some(&(_, major)) => self.newest(|v| v.0 == major),
+                None => Resolution::Unknown,
+            };
+        }
+
+        if let Some(range) = Range::parse(spec) {
+            return self.newest(|v| range.matches(v));
+        }
+        self.alias(spec, depth).unwrap_or(Resolution::Unknown)
+    }
+
+    fn newest(&self, accept: impl Fn(Version) -> bool) -> Resolution {
+        match self.versions.iter().copied().find(|&v| accept(v)) {
+            Some(v) => Resolution::Installed(v),
+            None => Resolution::Missing,
+        }
+    }
+
+    // nvm aliases are files holding another spec; fnm aliases are symlinks
+    // into a `vX.Y.Z` installation directory
+    fn alias(&self, name: &str, depth: usize) -> Option<Resolution> {
+        if depth == 0 {
+            return None;
+        }
+        for dir in &self.nvm_aliases {
+            if let Ok(target) = fs::read_to_string(dir.join(name)) {
+                return Some(self.resolve_within(&target, depth - 1));
+            }
+        }
+        for dir in &self.fnm_aliases {
+            if let Ok(target) = fs::read_link(dir.join(name)) {
+                let version = target
+                    .components()
+                    .find_map(|c| Version::parse(&c.as_os_str().to_string_lossy()))?;
+                return Some(self.newest(|v| v == version));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Caret,
+    Tilde,
+}
+
+/// A possibly partial version, where `20` and `20.x` leave minor and patch
+/// open.
+#[derive(Clone, Copy)]
+struct Partial(u64, Option<u64>, Option<u64>);
+
+impl Partial {
+    fn parse(s: &str) -> Option<Partial> {
+        let s = s.strip_prefix('v').unwrap_or(s);
+        if matches!(s, "*" | "x" | "X") {
+            return None;
+        }
+        let mut parts = s.split('.').map(|p| match p {
+            "*" | "x" | "X" => Ok(None),
+            p => p.parse().map(Some).map_err(|_| ()),
+        });
+        let major = parts.next()?.ok()??;
+        let minor = parts.next().unwrap_or(Ok(None)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(None)).ok()?;
+        if parts.next().is_some() || (minor.is_none() && patch.is_some()) {
+            return None;
+        }
+        Some(Partial(major, minor, patch))
+    }
+
+    fn floor(self) -> Version {
+        Version(self.0, self.1.unwrap_or(0), self.2.unwrap_or(0))
+    }
+
+    // First version past everything the partial covers
+    fn ceiling(self) -> Version {
+        match self {
+            Partial(major, None, _) => Version(major + 1, 0, 0),
+            Partial(major, Some(minor), None) => Version(major, minor + 1, 0),
+            Partial(major, Some(minor), Some(patch)) => Version(major, minor, patch + 1),
+        }
+    }
+
+    fn covers(self, v: Version) -> bool {
+        self.floor() <= v && v < self.ceiling()
+    }
+}
+
+/// The subset of npm semver ranges seen in `engines.node`: comparators
+/// joined by spaces, alternatives joined by `||`, hyphen ranges, and `x`
+/// wildcards.
+struct Range(Vec<Vec<(Op, Option<Partial>)>>);
+
+impl Range {
+    fn parse(spec: &str) -> Option<Range> {
+        let mut alternatives = Vec::new();
+        for alternative in spec.split("||") {
+            let tokens: Vec<&str> = alternative.split_whitespace().collect();
+            let comparators = match tokens.as_slice() {
+                [] => vec![(Op::Ge, None)],
+                [low, "-", high] => vec![
+                    (Op::Ge, Some(Partial::parse(low)?)),
+                    (Op::Le, Some(Partial::parse(high)?)),
+                ],
+                tokens => tokens
+                    .iter()
+                    .map(|t| Range::comparator(t))
+                    .collect::<Option<_>>()?,
+            };
+            alternatives.push(comparators);
+        }
+        Some(Range(alternatives))
+    }
+
+    fn comparator(token: &str) -> Option<(Op, Option<Partial>)> {
+        let (op, rest) = [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+            ("=", Op::Eq),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| token.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((Op::Eq, token));
+
+        if matches!(rest, "*" | "x" | "X") {
+            return Some((op, None));
+        }
+        Some((op, Some(Partial::parse(rest)?)))
+    }
+
+    fn matches(&self, v: Version) -> bool {
+        self.0
+            .iter()
+            .any(|all| all.iter().all(|&(op, partial)| satisfies(op, partial, v)))
+    }
+}
+
+fn satisfies(op: Op, partial: Option<Partial>, v: Version) -> bool {
+    // A bare wildcard matches everything, whatever the operator
+    let Some(p) = partial else {
+        return true;
+    };
+    match op {
+        Op::Eq => p.covers(v),
+        Op::Ge => v >= p.floor(),
+        Op::Gt => v >= p.ceiling(),
+        Op::Lt => v < p.floor(),
+        Op::Le => v < p.ceiling(),
+        // ^20.10 allows anything below 21, ^0.10 anything below 0.11
+        Op::Caret => {
+            let ceiling = match p {
+                Partial(0, Some(minor), _) => Version(0, minor + 1, 0),
+                Partial(major, _, _) => Version(major + 1, 0, 0),
+            };
+            p.floor() <= v && v < ceiling
+        }
+        // ~20.10 allows 20.10.x, ~20 allows 20.x
+        Op::Tilde => p.floor() <= v && v < Partial(p.0, p.1, None).ceiling(),
+    }
+}
+
+/// What to hand to `nvm use` or `fnm use` for `spec`: the installed version
+/// it resolves to, otherwise the spec itself when the managers understand it
+/// and can install it, such as `20`, `lts/iron` or an alias. `None` for a
+/// range no installed version satisfies.
+pub fn activation(spec: &str, installed: Option<&Installed>) -> Option<String> {
+    if let Some(Resolution::Installed(v)) = installed.map(|i| i.resolve(spec)) {
+        return Some(v.to_string());
+    }
+    let spec = spec.trim();
+    let version = spec
+        .strip_prefix('v')
+        .unwrap_or(spec)
+        .split('.')
+        .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    (version || Range::parse(spec).is_none()).then(|| spec.to_string())
+}
+
+/// The spec followed by the version it resolves to, such as `20 → 20.11.1`.
+/// Shown on its own when the two are the same.
+pub fn describe(spec: &str, resolved: Version) -> String {
+    let resolved = resolved.to_string();
+    if spec == resolved {
+        resolved
+    } else {
+        format!("{} → {}", spec, resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(spec: &str, version: &str) -> bool {
+        Range::parse(spec)
+            .unwrap()
+            .matches(Version::parse(version).unwrap())
+    }
+
+    fn installed(versions: &[&str]) -> Installed {
+        let mut versions: Vec<Version> = versions
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        versions.sort_unstable_by(|a, b| b.cmp(a));
+        Installed {
+            versions,
+            nvm_aliases: Vec::new(),
+            fnm_aliases: Vec::new(),
+        }
+    }
+
+    fn resolved(installed: &Installed, spec: &str) -> Option<String> {
+        match installed.resolve(spec) {
+            Resolution::Installed(v) => Some(v.to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn comparators() {
+        assert!(!matches(">20", "20.11.1"));
+        assert!(matches(">20", "21.0.0"));
+        assert!(matches(">=18 <21", "20.11.1"));
+        assert!(!matches(">=18 <21", "21.0.0"));
+        assert!(matches("<=20.10", "20.10.9"));
+        assert!(!matches("<=20.10", "20.11.0"));
+        assert!(matches("20.x", "20.0.0"));
+        assert!(!matches("20.x", "21.0.0"));
+        assert!(matches("*", "4.0.0"));
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        assert!(matches("^20.10", "20.19.0"));
+        assert!(!matches("^20.10", "20.9.0"));
+        assert!(!matches("^20.10", "21.0.0"));
+        assert!(matches("^0.10", "0.10.5"));
+        assert!(!matches("^0.10", "0.11.0"));
+        assert!(matches("~20.10", "20.10.3"));
+        assert!(!matches("~20.10", "20.11.0"));
+    }
+
+    #[test]
+    fn hyphen_ranges_include_the_upper_bound() {
+        assert!(matches("20 - 21", "20.0.0"));
+        assert!(matches("20 - 21", "21.9.9"));
+        assert!(!matches("20 - 21", "19.9.9"));
+        assert!(!matches("20 - 21", "22.0.0"));
+    }
+
+    #[test]
+    fn alternatives() {
+        assert!(matches("^18 || >=22", "18.19.0"));
+        assert!(!matches("^18 || >=22", "20.11.1"));
+        assert!(matches("^18 || >=22", "22.1.0"));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(Range::parse("lts/*").is_none());
+        assert!(Range::parse("20.x.1").is_none());
+        assert!(Range::parse(">= banana").is_none());
+    }
+
+    #[test]
+    fn specs_resolve_to_the_newest_match() {
+        let installed = installed(&["18.19.0", "20.9.0", "20.11.1", "21.6.0"]);
+        assert_eq!(resolved(&installed, "20").as_deref(), Some("20.11.1"));
+        assert_eq!(resolved(&installed, ">=18 <21").as_deref(), Some("20.11.1"));
+        assert_eq!(resolved(&installed, "node").as_deref(), Some("21.6.0"));
+        assert_eq!(resolved(&installed, "lts/*").as_deref(), Some("20.11.1"));
+        assert_eq!(
+            resolved(&installed, "lts/hydrogen").as_deref(),
+            Some("18.19.0")
+        );
+        assert!(matches!(installed.resolve(">=22"), Resolution::Missing));
+        assert!(matches!(installed.resolve("system"), Resolution::Unknown));
+    }
+
+    #[test]
+    fn activation_prefers_installed_versions() {
+        let installed = installed(&["20.11.1"]);
+        assert_eq!(
+            activation(">=18", Some(&installed)).as_deref(),
+            Some("20.11.1")
+        );
+        // Left to nvm or fnm to install
+        assert_eq!(activation("22", Some(&installed)).as_deref(), Some("22"));
+        assert_eq!(activation("lts/jod", None).as_deref(), Some("lts/jod"));
+        // Nothing can install a range
+        assert_eq!(activation(">=22", Some(&installed)), None);
+        assert_eq!(activation("^20", None), None);
+    }
+}
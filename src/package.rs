@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::project::repo_ancestors;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackageManager {
+    Npm,
+    /// Yarn 1.x.
+    Yarn,
+    /// Yarn 2 and later, with its own lockfile format and CLI.
+    YarnBerry,
+    Pnpm,
+    Bun,
+}
+
+impl fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::YarnBerry => "yarn berry",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Bun => "bun",
+        })
+    }
+}
+
+impl PackageManager {
+    fn program(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Yarn | PackageManager::YarnBerry => "yarn",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// Command line that runs a `package.json` script.
+    pub fn run_args(self, script: &str) -> Vec<String> {
+        vec![self.program().into(), "run".into(), script.into()]
+    }
+
+    /// Command line that installs the project's dependencies.
+    pub fn install_args(self) -> Vec<String> {
+        vec![self.program().into(), "install".into()]
+    }
+}
+
+// Lockfiles, checked in this order within a directory
+const LOCKFILES: &[(&str, PackageManager)] = &[
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("package-lock.json", PackageManager::Npm),
+    ("npm-shrinkwrap.json", PackageManager::Npm),
+];
+
+/// Finds the package manager a project uses.
+///
+/// Walking up from `path` to the repository root, the nearest directory
+/// with a signal wins. Within a directory the `packageManager` field of
+/// `package.json` (as used by Corepack) beats lockfiles, since it is what
+/// the team declared rather than what someone last ran. A `yarn.lock` is
+/// Yarn Berry's when it has a `__metadata` header or sits next to a
+/// `.yarnrc.yml`.
+pub fn detect(path: &Path) -> Option<PackageManager> {
+    for dir in repo_ancestors(path) {
+        if let Some(manager) = declared(&dir.join("package.json")) {
+            return Some(manager);
+        }
+        for &(file, manager) in LOCKFILES {
+            let lockfile = dir.join(file);
+            if !lockfile.is_file() {
+                continue;
+            }
+            if manager == PackageManager::Yarn && is_berry(dir, &lockfile) {
+                return Some(PackageManager::YarnBerry);
+            }
+            return Some(manager);
+        }
+    }
+    None
+}
+
+// `"packageManager": "pnpm@8.15.1+sha256.abc"`
+fn declared(package_json: &Path) -> Option<PackageManager> {
+    let content = fs::read_to_string(package_json).ok()?;
+    let json: Value = serde_json::from_str(&content).ok()?;
+    let field = json.get("packageManager")?.as_str()?;
+    let (name, version) = field.split_once('@').unwrap_or((field, ""));
+    match name {
+        "npm" => Some(PackageManager::Npm),
+        "pnpm" => Some(PackageManager::Pnpm),
+        "bun" => Some(PackageManager::Bun),
+        "yarn" if version.starts_with("1.") => Some(PackageManager::Yarn),
+        "yarn" => Some(PackageManager::YarnBerry),
+        _ => None,
+    }
+}
+
+fn is_berry(dir: &Path, lockfile: &Path) -> bool {
+    dir.join(".yarnrc.yml").is_file()
+        || fs::read_to_string(lockfile).is_ok_and(|lock| lock.contains("\n__metadata:"))
+}
+
+/// The `scripts` of the project's own `package.json`, as name and command,
+/// sorted by name.
+pub fn scripts(path: &Path) -> io::Result<Vec<(String, String)>> {
+    let content = fs::read_to_string(path.join("package.json"))?;
+    let json: Value = serde_json::from_str(&content)?;
+    let scripts = match json.get("scripts") {
+        Some(Value::Object(scripts)) => scripts
+            .iter()
+            .filter_map(|(name, command)| Some((name.clone(), command.as_str()?.to_string())))
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(scripts)
+}
+
+/// A package inside a monorepo workspace.
+#[derive(Clone)]
+pub struct WorkspacePackage {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Lists the packages of the workspace rooted at `root`, sorted by path.
+///
+/// Package globs come from the `workspaces` field of `package.json` (npm,
+/// Yarn, bun), `pnpm-workspace.yaml`, `lerna.json` and the app and library
+/// directories of `nx.json`. Turborepo builds on the package manager's
+/// workspaces, so `turbo.json` needs nothing of its own. Globs may use `*`,
+/// `?` and `**`, and a leading `!` excludes matches. Ordinary projects have
+/// no packages.
+pub fn workspace_packages(root: &Path) -> Vec<WorkspacePackage> {
+    let read_json = |file: &str| {
+        fs::read_to_string(root.join(file))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    };
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect()
+    };
+
+    let mut patterns = Vec::new();
+    if let Some(package) = read_json("package.json") {
+        let workspaces = package.get("workspaces");
+        // Yarn classic also allows { "packages": [...], "nohoist": [...] }
+        patterns.extend(strings(workspaces));
+        patterns.extend(strings(workspaces.and_then(|w| w.get("packages"))));
+    }
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        patterns.extend(pnpm_workspace_globs(&content));
+    }
+    if let Some(lerna) = read_json("lerna.json") {
+        match lerna.get("packages") {
+            Some(packages) => patterns.extend(strings(Some(packages))),
+            None => patterns.push("packages/*".to_string()),
+        }
+    }
+    if let Some(nx) = read_json("nx.json") {
+        let layout = nx.get("workspaceLayout");
+        for (key, default) in [("appsDir", "apps"), ("libsDir", "libs")] {
+            let dir = layout
+                .and_then(|l| l.get(key))
+                .and_then(Value::as_str)
+                .unwrap_or(default);
+            patterns.push(format!("{}/**", dir));
+        }
+    }
+
+    let (exclude, include): (Vec<String>, Vec<String>) =
+        patterns.into_iter().partition(|p| p.starts_with('!'));
+    let excluded: Vec<PathBuf> = exclude
+        .iter()
+        .flat_map(|pattern| expand_glob(root, &pattern[1..]))
+        .collect();
+
+    let mut paths: Vec<PathBuf> = include
+        .iter()
+        .flat_map(|pattern| expand_glob(root, pattern))
+        .filter(|path| path != root && !excluded.contains(path))
+        .filter(|path| path.join("package.json").is_file() || path.join("project.json").is_file())
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .map(|path| WorkspacePackage {
+            name: package_name(&path),
+            path,
+        })
+        .collect()
+}
+
+// The `packages:` list of pnpm-workspace.yaml. Only that block sequence is
+// read, which is all pnpm puts in the file.
+fn pnpm_workspace_globs(content: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = line.trim() == "packages:";
+            continue;
+        }
+        if in_packages && let Some(item) = line.trim().strip_prefix('-') {
+            let item = item.trim().trim_matches(['\'', '"']);
+            if !item.is_empty() {
+                globs.push(item.to_string());
+            }
+        }
+    }
+    globs
+}
+
+// Name from package.json or nx's project.json, else the directory name
+fn package_name(path: &Path) -> String {
+    ["package.json", "project.json"]
+        .iter()
+        .find_map(|file| {
+            let content = fs::read_to_string(path.join(file)).ok()?;
+            let json: Value = serde_json::from_str(&content).ok()?;
+            json.get("name")?.as_str().map(str::to_string)
+        })
+        .unwrap_or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+fn expand_glob(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let pattern = pattern.trim_start_matches("./");
+    let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let mut found = Vec::new();
+    expand_segments(root, &segments, &mut found);
+    found
+}
+
+fn expand_segments(dir: &Path, segments: &[&str], found: &mut Vec<PathBuf>) {
+    let Some((&first, rest)) = segments.split_first() else {
+        found.push(dir.to_path_buf());
+        return;
+    };
+    if first == "**" {
+        expand_segments(dir, rest, found);
+        for child in subdirs(dir) {
+            expand_segments(&child, segments, found);
+        }
+    } else if first.contains(['*', '?']) {
+        for child in subdirs(dir) {
+            let name = child.file_name().unwrap_or_default().to_string_lossy();
+            if wildcard_match(first, &name) {
+                expand_segments(&child, rest, found);
+            }
+        }
+    } else if dir.join(first).is_dir() {
+        expand_segments(&dir.join(first), rest, found);
+    }
+}
+
+// Subdirectories a glob may enter: no hidden ones, node_modules or symlinks
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.')
+                && name != "node_modules"
+                && entry.file_type().is_ok_and(|t| t.is_dir())
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+// `*` matches any run of characters, `?` a single one
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and where its match currently ends
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("*", "web"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("app-*", "app-web"));
+        assert!(!wildcard_match("app-*", "lib-web"));
+        assert!(wildcard_match("*-web", "app-web"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("a*b*c", "axxbyy"));
+        assert!(wildcard_match("v?", "v1"));
+        assert!(!wildcard_match("v?", "v12"));
+        assert!(!wildcard_match("web", "webs"));
+    }
+
+    #[test]
+    fn pnpm_packages_block() {
+        let yaml = "\
+# workspace
+packages:
+  - 'apps/*'
+  - \"packages/**\"   # everything
+  - '!**/test/**'
+catalog:
+  - react
+";
+        assert_eq!(
+            pnpm_workspace_globs(yaml),
+            ["apps/*", "packages/**", "!**/test/**"]
+        );
+    }
+}
@@ -1,103 +1,605 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::{env, io};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::git::GitIdentity;
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::node;
+use crate::package::{self, PackageManager};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Project {
     pub name: String,
     pub path: PathBuf,
     pub last_opened: Option<DateTime<Utc>>,
     pub node_version: Option<String>,
+    /// Where `node_version` was read from, see `node::resolve`.
+    #[serde(default)]
+    pub node_source: Option<String>,
+    /// Detected from `packageManager` or lockfiles, see `package::detect`.
+    #[serde(default)]
+    pub package_manager: Option<PackageManager>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the project was opened, oldest first, capped at `HISTORY_LIMIT`.
+    #[serde(default)]
+    pub history: Vec<DateTime<Utc>>,
+    /// Position among pinned projects, which always sort first.
+    #[serde(default)]
+    pub pinned: Option<u32>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    /// The repository's `nodash.toml` as last read, merged in by the
+    /// `all_*` accessors.
+    #[serde(default)]
+    pub manifest: Option<Manifest>,
+    /// Recognises the repository after a move, see `nodash prune`.
+    #[serde(default)]
+    pub git: Option<GitIdentity>,
+    /// The manifest allowed to export its env files and run its `pre_open`
+    /// commands, see `nodash trust`. Any change has to be trusted again.
+    #[serde(default)]
+    pub trusted_manifest: Option<Manifest>,
 }
 
-pub fn detect_node_version(path: &PathBuf) -> io::Result<String> {
-    // Check if there's a .nvmrc file
-    let nvmrc_path = path.join(".nvmrc");
-    if nvmrc_path.exists() {
-        let content = std::fs::read_to_string(nvmrc_path)?;
-        let version = content.trim();
+/// A named way to start a project's shell, such as `dev` running `pnpm dev`
+/// with `PORT=3001`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Run once Node is active, written for the user's shell.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Exported before `command` runs and kept in the shell afterwards.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
 
-        // Remove 'v' prefix if present and return clean version
-        let clean_version = version.strip_prefix('v').unwrap_or(version);
-        Ok(clean_version.to_string())
-    } else {
-        Err(io::Error::new(
+impl Profile {
+    /// One-line summary such as `PORT=3001 pnpm dev`.
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        parts.extend(self.command.clone());
+        parts.join(" ")
+    }
+}
+
+pub const HISTORY_LIMIT: usize = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortMode {
+    /// Frequency weighted by recency, like zoxide.
+    Frecency,
+    Recent,
+    Name,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 3] = [SortMode::Frecency, SortMode::Recent, SortMode::Name];
+
+    pub fn parse(value: &str) -> io::Result<SortMode> {
+        SortMode::ALL
+            .into_iter()
+            .find(|mode| mode.label() == value)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown sort mode '{}', expected frecency, recent or name",
+                        value
+                    ),
+                )
+            })
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Frecency => "frecency",
+            SortMode::Recent => "recent",
+            SortMode::Name => "name",
+        }
+    }
+
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Frecency => SortMode::Recent,
+            SortMode::Recent => SortMode::Name,
+            SortMode::Name => SortMode::Frecency,
+        }
+    }
+}
+
+impl Project {
+    /// A fresh, never opened project with its tooling detected. Fails on a
+    /// broken `nodash.toml`.
+    pub fn new(name: String, path: PathBuf) -> io::Result<Project> {
+        let mut project = Project {
+            name,
+            path,
+            last_opened: None,
+            node_version: None,
+            node_source: None,
+            package_manager: None,
+            tags: Vec::new(),
+            history: Vec::new(),
+            pinned: None,
+            profiles: Vec::new(),
+            manifest: None,
+            git: None,
+            trusted_manifest: None,
+        };
+        project.detect_tooling()?;
+        Ok(project)
+    }
+
+    /// Re-reads the Node version, its source, the package manager, the git
+    /// identity and `nodash.toml` from the project files. A broken manifest
+    /// is reported after everything else has been refreshed and the previous
+    /// one is kept.
+    pub fn detect_tooling(&mut self) -> io::Result<()> {
+        let resolved = node::resolve(&self.path);
+        self.node_source = resolved.as_ref().map(|v| v.source.clone());
+        self.node_version = resolved.map(|v| v.version);
+        self.package_manager = package::detect(&self.path);
+        // Keep what is known when git is unavailable
+        if let Some(identity) = GitIdentity::read(&self.path) {
+            self.git = Some(identity);
+        }
+        self.manifest = Manifest::load(&self.path)?;
+        Ok(())
+    }
+
+    /// The user's tags together with those from `nodash.toml`, sorted.
+    pub fn all_tags(&self) -> Vec<&str> {
+        let shared = self.manifest.iter().flat_map(|m| m.tags.iter());
+        let mut tags: Vec<&str> = self.tags.iter().chain(shared).map(String::as_str).collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+
+    /// The user's profiles followed by those from `nodash.toml` that they do
+    /// not override.
+    pub fn all_profiles(&self) -> Vec<Profile> {
+        let mut profiles = self.profiles.clone();
+        for (name, spec) in self.manifest.iter().flat_map(|m| m.profiles.iter()) {
+            if !profiles.iter().any(|p| &p.name == name) {
+                profiles.push(Profile {
+                    name: name.clone(),
+                    command: spec.command.clone(),
+                    env: spec.env.clone(),
+                });
+            }
+        }
+        profiles
+    }
+
+    /// Whether `nodash.toml`, if any, is the one the user trusted.
+    pub fn manifest_trusted(&self) -> bool {
+        self.manifest.is_none() || self.manifest == self.trusted_manifest
+    }
+
+    /// Whether the project directory is gone, e.g. deleted or moved.
+    pub fn is_missing(&self) -> bool {
+        !self.path.is_dir()
+    }
+
+    /// Why a missing project cannot be opened.
+    pub fn missing_error(&self) -> io::Error {
+        io::Error::new(
             io::ErrorKind::NotFound,
-            "No .nvmrc file found",
-        ))
+            format!(
+                "'{}' no longer exists at {}; run 'nodash prune' to remove or relocate it",
+                self.name,
+                self.path.display()
+            ),
+        )
+    }
+
+    /// Notes that the project was just opened.
+    pub fn record_open(&mut self) {
+        let now = Utc::now();
+        self.last_opened = Some(now);
+        self.history.push(now);
+        if self.history.len() > HISTORY_LIMIT {
+            self.history.drain(..self.history.len() - HISTORY_LIMIT);
+        }
     }
+
+    /// Zoxide-style score: every open counts, recent ones count more.
+    pub fn frecency(&self, now: DateTime<Utc>) -> f64 {
+        self.history
+            .iter()
+            .map(|ts| {
+                let age = now.signed_duration_since(*ts);
+                if age < Duration::hours(1) {
+                    4.0
+                } else if age < Duration::days(1) {
+                    2.0
+                } else if age < Duration::weeks(1) {
+                    0.5
+                } else {
+                    0.25
+                }
+            })
+            .fold(0.0, |score, weight| score + weight)
+    }
+
+    /// Matches a TUI search query.
+    ///
+    /// `tag:NAME` terms must all match one of the project's tags; the
+    /// remaining words are a case-insensitive substring of the name or path.
+    pub fn matches_query(&self, query: &str) -> bool {
+        let mut text = Vec::new();
+        for term in query.split_whitespace() {
+            match term.strip_prefix("tag:") {
+                Some(tag) if !tag.is_empty() => {
+                    if !self.all_tags().iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                        return false;
+                    }
+                }
+                _ => text.push(term),
+            }
+        }
+        if text.is_empty() {
+            return true;
+        }
+        let query = text.join(" ").to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.path.to_string_lossy().to_lowercase().contains(&query)
+    }
+
+    /// Adds tags, ignoring duplicates. Returns the tags that were new.
+    pub fn add_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let mut added = Vec::new();
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !self.tags.iter().any(|t| t == tag) {
+                self.tags.push(tag.to_string());
+                added.push(tag.to_string());
+            }
+        }
+        self.tags.sort();
+        added
+    }
+
+    pub fn profile(&self, name: &str) -> io::Result<Profile> {
+        self.all_profiles()
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| self.missing_profile(name))
+    }
+
+    fn missing_profile(&self, name: &str) -> io::Error {
+        let profiles = self.all_profiles();
+        let known: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        let hint = if known.is_empty() {
+            "it has no profiles".to_string()
+        } else {
+            format!("known profiles: {}", known.join(", "))
+        };
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("'{}' has no profile '{}', {}", self.name, name, hint),
+        )
+    }
+
+    /// Adds a profile or replaces the one with the same name. Returns whether
+    /// one was replaced.
+    pub fn set_profile(&mut self, profile: Profile) -> bool {
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => {
+                *existing = profile;
+                true
+            }
+            None => {
+                self.profiles.push(profile);
+                false
+            }
+        }
+    }
+
+    /// Removes one of the user's profiles; shared ones live in `nodash.toml`.
+    pub fn remove_profile(&mut self, name: &str) -> io::Result<Profile> {
+        if let Some(idx) = self.profiles.iter().position(|p| p.name == name) {
+            return Ok(self.profiles.remove(idx));
+        }
+        if self.profile(name).is_ok() {
+            return Err(io::Error::other(format!(
+                "Profile '{}' comes from {}, edit it there",
+                name,
+                self.path.join(MANIFEST_FILE).display()
+            )));
+        }
+        Err(self.missing_profile(name))
+    }
+
+    /// Removes tags. Returns the tags that were actually present.
+    pub fn remove_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let removed: Vec<String> = self
+            .tags
+            .iter()
+            .filter(|t| tags.iter().any(|r| r.trim() == t.as_str()))
+            .cloned()
+            .collect();
+        self.tags.retain(|t| !removed.contains(t));
+        removed
+    }
+}
+
+/// `path` as it is stored and compared: absolute, without `.` or `..`.
+/// Existing paths are canonicalized, like the working directory `which`
+/// sees; others, such as a store file yet to be created, are cleaned
+/// lexically.
+pub fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    if let Ok(path) = fs::canonicalize(path) {
+        return Ok(path);
+    }
+    let mut clean = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            other => clean.push(other),
+        }
+    }
+    Ok(clean)
+}
+
+/// `path` and its parents up to the repository root, the first directory
+/// holding `.git`. Outside a repository this walks up to `/`.
+pub fn repo_ancestors(path: &Path) -> impl Iterator<Item = &Path> {
+    let mut past_root = false;
+    path.ancestors().take_while(move |dir| {
+        let take = !past_root;
+        past_root = dir.join(".git").exists();
+        take
+    })
 }
 
-fn sort_projects(projects: &mut Vec<Project>) {
-    projects.sort_by(|a, b| match (a.last_opened, b.last_opened) {
+fn by_last_opened(a: &Project, b: &Project) -> Ordering {
+    match (a.last_opened, b.last_opened) {
         (Some(a_date), Some(b_date)) => b_date.cmp(&a_date),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
         (None, None) => a.name.cmp(&b.name),
-    });
+    }
 }
 
-pub fn add_current_directory() -> io::Result<()> {
-    let current_dir = env::current_dir()?;
-    let mut projects = load_projects()?;
+/// Sorts pinned projects first, in pin order, and the rest by `mode`.
+pub fn sort_projects(projects: &mut [Project], mode: SortMode) {
+    match mode {
+        SortMode::Recent => projects.sort_by(by_last_opened),
+        SortMode::Name => projects.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then_with(|| a.path.cmp(&b.path))
+        }),
+        SortMode::Frecency => {
+            let now = Utc::now();
+            projects.sort_by(|a, b| {
+                b.frecency(now)
+                    .total_cmp(&a.frecency(now))
+                    .then_with(|| by_last_opened(a, b))
+            });
+        }
+    }
+    // Stable sort, so unpinned projects keep the order chosen above
+    projects.sort_by_key(|p| p.pinned.unwrap_or(u32::MAX));
+}
+
+/// Renumbers pins to 1..=n, keeping their relative order.
+fn normalize_pins(projects: &mut [Project]) {
+    let mut pinned: Vec<&mut Project> =
+        projects.iter_mut().filter(|p| p.pinned.is_some()).collect();
+    pinned.sort_by_key(|p| p.pinned);
+    for (pos, p) in pinned.into_iter().enumerate() {
+        p.pinned = Some(pos as u32 + 1);
+    }
+}
+
+/// Pins the project at `position` (1-based, clamped; at the end when
+/// `None`), or moves it there if it is already pinned.
+pub fn pin_project(projects: &mut [Project], idx: usize, position: Option<u32>) {
+    let count = projects.iter().filter(|p| p.pinned.is_some()).count() as u32;
+    let last = if projects[idx].pinned.is_some() {
+        count
+    } else {
+        count + 1
+    };
+    let target = position.unwrap_or(last).clamp(1, last);
+
+    projects[idx].pinned = None;
+    normalize_pins(projects);
+    for p in projects.iter_mut() {
+        if let Some(pos) = p.pinned.as_mut()
+            && *pos >= target
+        {
+            *pos += 1;
+        }
+    }
+    projects[idx].pinned = Some(target);
+}
+
+pub fn unpin_project(projects: &mut [Project], idx: usize) {
+    projects[idx].pinned = None;
+    normalize_pins(projects);
+}
+
+/// Looks up a project by exact name, falling back to its path.
+///
+/// Returns an error when nothing matches or when several projects share the
+/// requested name, so scripted callers never act on the wrong entry.
+pub fn find_project(projects: &[Project], query: &str) -> io::Result<usize> {
+    let by_name: Vec<usize> = projects
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.name == query)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    match by_name.as_slice() {
+        [idx] => return Ok(*idx),
+        [] => {}
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Multiple projects are named '{}', use the path instead",
+                    query
+                ),
+            ));
+        }
+    }
+
+    let query_path = absolute_path(Path::new(query)).unwrap_or_else(|_| PathBuf::from(query));
+    projects
+        .iter()
+        .position(|p| p.path == query_path)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No project named '{}'", query),
+            )
+        })
+}
+
+/// Finds the project whose directory contains `path`, preferring the deepest
+/// one when projects are nested.
+pub fn find_project_containing(projects: &[Project], path: &Path) -> Option<usize> {
+    projects
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| path.starts_with(&p.path))
+        .max_by_key(|(_, p)| p.path.components().count())
+        .map(|(idx, _)| idx)
+}
+
+/// Builds a new project for `path`, rejecting paths that are already stored.
+pub fn add_project(
+    projects: &mut Vec<Project>,
+    path: &Path,
+    name: Option<&str>,
+) -> io::Result<Project> {
+    let name = name.map(str::trim);
+    if name == Some("") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Project name cannot be empty",
+        ));
+    }
+    let path = absolute_path(path)?;
+    if !path.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a directory", path.display()),
+        ));
+    }
 
     // Check if project already exists
-    if projects.iter().any(|p| p.path == current_dir) {
-        println!("Project already exists in nodash");
-        return Ok(());
+    if projects.iter().any(|p| p.path == path) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "Project already exists in nodash",
+        ));
     }
 
-    let project_name = current_dir
+    let dir_name = path
         .file_name()
         .and_then(|name| name.to_str())
         .unwrap_or("Unknown")
         .to_string();
+    let mut project = Project::new(name.map(str::to_string).unwrap_or(dir_name), path)?;
+    // The team's name for it, unless the user picked one
+    if let (None, Some(shared)) = (
+        name,
+        project.manifest.as_ref().and_then(|m| m.name.as_deref()),
+    ) {
+        let shared = shared.trim();
+        if !shared.is_empty() {
+            project.name = shared.to_string();
+        }
+    }
 
-    // Get node version before creating the project
-    let node_version = detect_node_version(&current_dir).ok();
+    projects.push(project.clone());
+    Ok(project)
+}
 
-    let project = Project {
-        name: project_name.clone(),
-        path: current_dir.clone(),
-        last_opened: None,
-        node_version: node_version.clone(),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    projects.push(project);
-    save_projects(&projects)?;
+    fn projects(names: &[&str]) -> Vec<Project> {
+        names
+            .iter()
+            .map(|name| {
+                let path = format!("/code/{}", name);
+                serde_json::from_value(json!({ "name": name, "path": path })).unwrap()
+            })
+            .collect()
+    }
 
-    println!("✅ Added '{}' to nodash", project_name);
-    println!("   Path: {}", current_dir.display());
-    if let Some(version) = node_version {
-        println!("   Node version: {}", version);
-    } else {
-        println!("   No .nvmrc file found");
+    fn pins(projects: &[Project]) -> Vec<Option<u32>> {
+        projects.iter().map(|p| p.pinned).collect()
     }
 
-    Ok(())
-}
+    #[test]
+    fn new_pins_go_at_the_end() {
+        let mut list = projects(&["api", "web", "cli"]);
+        pin_project(&mut list, 1, None);
+        pin_project(&mut list, 0, None);
+        assert_eq!(pins(&list), [Some(2), Some(1), None]);
+    }
 
-pub fn load_projects() -> io::Result<Vec<Project>> {
-    let file = dirs::home_dir().unwrap().join(".nodash_projects.json");
-    if !file.exists() {
-        return Ok(vec![]);
+    #[test]
+    fn pinned_projects_move_up_and_down() {
+        let mut list = projects(&["api", "web", "cli"]);
+        for idx in 0..3 {
+            pin_project(&mut list, idx, None);
+        }
+
+        // `]` on the first pin
+        pin_project(&mut list, 0, Some(2));
+        assert_eq!(pins(&list), [Some(2), Some(1), Some(3)]);
+
+        // `[` on the last pin
+        pin_project(&mut list, 2, Some(2));
+        assert_eq!(pins(&list), [Some(3), Some(1), Some(2)]);
     }
-    let data = std::fs::read_to_string(file)?;
-    let projects: Vec<Project> = serde_json::from_str(&data)?;
 
-    // Projects are already sorted when saved, no need to sort again
-    Ok(projects)
-}
+    #[test]
+    fn positions_are_clamped() {
+        let mut list = projects(&["api", "web", "cli"]);
+        pin_project(&mut list, 0, None);
+        pin_project(&mut list, 1, Some(9));
+        assert_eq!(pins(&list), [Some(1), Some(2), None]);
 
-pub fn save_projects(projects: &[Project]) -> io::Result<()> {
-    let file = dirs::home_dir().unwrap().join(".nodash_projects.json");
+        // Already last, so moving further down changes nothing
+        pin_project(&mut list, 1, Some(3));
+        assert_eq!(pins(&list), [Some(1), Some(2), None]);
 
-    // Sort before saving to maintain order
-    let mut sorted_projects = projects.to_vec();
-    sort_projects(&mut sorted_projects);
+        pin_project(&mut list, 2, Some(0));
+        assert_eq!(pins(&list), [Some(2), Some(3), Some(1)]);
+    }
 
-    let data = serde_json::to_string_pretty(&sorted_projects)?;
-    std::fs::write(file, data)?;
-    Ok(())
+    #[test]
+    fn unpinning_renumbers_the_rest() {
+        let mut list = projects(&["api", "web", "cli"]);
+        for idx in 0..3 {
+            pin_project(&mut list, idx, None);
+        }
+        unpin_project(&mut list, 1);
+        assert_eq!(pins(&list), [Some(1), None, Some(2)]);
+    }
 }
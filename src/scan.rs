@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DEPTH: usize = 3;
+
+/// Finds Node projects, directories holding a `package.json`, at most `depth`
+/// levels below `root`.
+///
+/// A project's own subdirectories are not searched, so workspace packages
+/// stay part of the repository that contains them. Hidden directories,
+/// `node_modules` and symlinks are skipped, as are directories that cannot
+/// be read.
+pub fn find_projects(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    walk(
+        root,
+        depth,
+        &|dir| dir.join("package.json").is_file(),
+        &mut found,
+    );
+    found.sort();
+    found
+}
+
+/// Finds git repositories at most `depth` levels below `root`, skipping the
+/// same directories as `find_projects` and not searching inside a
+/// repository.
+pub fn find_repositories(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    walk(root, depth, &|dir| dir.join(".git").exists(), &mut found);
+    found.sort();
+    found
+}
+
+fn walk(dir: &Path, depth: usize, is_match: &dyn Fn(&Path) -> bool, found: &mut Vec<PathBuf>) {
+    if is_match(dir) {
+        found.push(dir.to_path_buf());
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "node_modules" {
+            continue;
+        }
+        // file_type does not follow symlinks, which keeps loops out
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            walk(&entry.path(), depth - 1, is_match, found);
+        }
+    }
+}
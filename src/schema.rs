@@ -0,0 +1,227 @@
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::io;
+
+use crate::project::Project;
+
+/// Version written to the store envelope. Bump it together with a new entry
+/// in `MIGRATIONS` whenever `Project` changes shape, so older nodash builds
+/// refuse the file instead of silently dropping fields they do not know.
+pub const STORE_VERSION: u64 = 10;
+
+type Migration = fn(Value) -> Value;
+
+// MIGRATIONS[n] upgrades a version `n` document to version `n + 1`
+const MIGRATIONS: &[Migration] = &[
+    // v0 was a bare array of projects
+    |doc| json!({ "version": 1, "projects": doc }),
+    // v2 added tags
+    |doc| add_field(doc, 2, "tags", json!([])),
+    // v3 added the open history, seeded with the last open
+    |mut doc| {
+        if let Some(Value::Array(projects)) = doc.get_mut("projects") {
+            for project in projects.iter_mut().filter_map(Value::as_object_mut) {
+                let seed = match project.get("last_opened") {
+                    Some(ts) if !ts.is_null() => json!([ts]),
+                    _ => json!([]),
+                };
+                project.entry("history").or_insert(seed);
+            }
+        }
+        doc["version"] = json!(3);
+        doc
+    },
+    // v4 added pinning
+    |doc| add_field(doc, 4, "pinned", Value::Null),
+    // v5 recorded where the Node version came from; before, only .nvmrc was read
+    |mut doc| {
+        if let Some(Value::Array(projects)) = doc.get_mut("projects") {
+            for project in projects.iter_mut().filter_map(Value::as_object_mut) {
+                let source = match project.get("node_version") {
+                    Some(v) if !v.is_null() => json!(".nvmrc"),
+                    _ => Value::Null,
+                };
+                project.entry("node_source").or_insert(source);
+            }
+        }
+        doc["version"] = json!(5);
+        doc
+    },
+    // v6 added the package manager
+    |doc| add_field(doc, 6, "package_manager", Value::Null),
+    // v7 added launch profiles
+    |doc| add_field(doc, 7, "profiles", json!([])),
+    // v8 kept a copy of the repository's nodash.toml
+    |doc| add_field(doc, 8, "manifest", Value::Null),
+    // v9 added the git identity used to find moved projects
+    |doc| add_field(doc, 9, "git", Value::Null),
+    // v10 remembered which nodash.toml the user trusted
+    |doc| add_field(doc, 10, "trusted_manifest", Value::Null),
+];
+
+// Sets `field` to `default` on every project that lacks it
+fn add_field(mut doc: Value, version: u64, field: &str, default: Value) -> Value {
+    if let Some(Value::Array(projects)) = doc.get_mut("projects") {
+        for project in projects.iter_mut().filter_map(Value::as_object_mut) {
+            project.entry(field).or_insert_with(|| default.clone());
+        }
+    }
+    doc["version"] = json!(version);
+    doc
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    projects: &'a [Project],
+}
+
+/// An entry that could not be read back, kept for reporting.
+pub struct SkippedEntry {
+    pub index: usize,
+    pub name: Option<String>,
+    pub reason: String,
+}
+
+pub struct Decoded {
+    pub projects: Vec<Project>,
+    pub skipped: Vec<SkippedEntry>,
+}
+
+pub fn encode(projects: &[Project]) -> io::Result<String> {
+    let envelope = Envelope {
+        version: STORE_VERSION,
+        projects,
+    };
+    Ok(serde_json::to_string_pretty(&envelope)?)
+}
+
+fn document_version(doc: &Value) -> io::Result<u64> {
+    match doc {
+        Value::Array(_) => Ok(0),
+        Value::Object(map) => map.get("version").and_then(Value::as_u64).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Store has no schema version")
+        }),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Store is neither a project list nor a versioned envelope",
+        )),
+    }
+}
+
+/// Parses a store document of any known version.
+///
+/// Entries that fail to deserialize are skipped and reported instead of
+/// failing the whole load. A document from a newer nodash is rejected with
+/// `ErrorKind::Unsupported`, as saving it back would lose data.
+pub fn decode(data: &str) -> io::Result<Decoded> {
+    let mut doc: Value = serde_json::from_str(data)?;
+    let version = document_version(&doc)?;
+
+    if version > STORE_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "Store uses schema version {}, but this nodash only understands up to {}. \
+                 Run 'nodash update'",
+                version, STORE_VERSION
+            ),
+        ));
+    }
+    for migrate in &MIGRATIONS[version as usize..] {
+        doc = migrate(doc);
+    }
+
+    let entries = match doc.get_mut("projects").map(Value::take) {
+        Some(Value::Array(entries)) => entries,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Store has no project list",
+            ));
+        }
+    };
+
+    let mut projects = Vec::with_capacity(entries.len());
+    let mut skipped = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let name = entry
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        match serde_json::from_value::<Project>(entry) {
+            Ok(project) => projects.push(project),
+            Err(e) => skipped.push(SkippedEntry {
+                index,
+                name,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    Ok(Decoded { projects, skipped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_array_is_upgraded() {
+        let decoded = decode(
+            r#"[{"name": "api", "path": "/code/api",
+                "last_opened": "2024-05-01T10:00:00Z", "node_version": "20"}]"#,
+        )
+        .unwrap();
+        assert!(decoded.skipped.is_empty());
+        let p = &decoded.projects[0];
+        assert_eq!(p.name, "api");
+        assert!(p.tags.is_empty());
+        assert_eq!(p.history, p.last_opened.into_iter().collect::<Vec<_>>());
+        assert!(p.pinned.is_none());
+        assert_eq!(p.node_source.as_deref(), Some(".nvmrc"));
+        assert!(p.trusted_manifest.is_none());
+    }
+
+    #[test]
+    fn unreadable_entries_are_skipped() {
+        let decoded = decode(
+            r#"{"version": 1, "projects": [
+                {"name": "ok", "path": "/a", "last_opened": null, "node_version": null},
+                {"name": "bad", "path": 42},
+                "oops"
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(decoded.projects.len(), 1);
+        assert_eq!(decoded.projects[0].name, "ok");
+        let skipped: Vec<(usize, Option<&str>)> = decoded
+            .skipped
+            .iter()
+            .map(|s| (s.index, s.name.as_deref()))
+            .collect();
+        assert_eq!(skipped, [(1, Some("bad")), (2, None)]);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let data = format!(r#"{{"version": {}, "projects": []}}"#, STORE_VERSION + 1);
+        let err = decode(&data).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn documents_without_a_version_are_invalid() {
+        let err = decode(r#"{"projects": []}"#).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn encoded_stores_read_back_unchanged() {
+        let projects = decode(r#"[{"name": "api", "path": "/code/api"}]"#)
+            .unwrap()
+            .projects;
+        let decoded = decode(&encode(&projects).unwrap()).unwrap();
+        assert_eq!(decoded.projects, projects);
+    }
+}
@@ -1,10 +1,13 @@
-use crate::project::{Project, detect_node_version};
-use chrono::Utc;
+use crate::config::Config;
+use crate::manifest::read_env_file;
+use crate::node::{self, Installed};
+use crate::project::{Profile, Project};
+use crate::terminal::{Launch, open_terminal};
 use std::env;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::Command;
 
 fn get_current_shell() -> String {
     if let Ok(shell) = env::var("SHELL") {
@@ -21,46 +24,174 @@ fn is_zsh_shell(shell: &str) -> bool {
     shell.contains("zsh")
 }
 
-fn get_terminal_emulator() -> Option<String> {
-    // Detect some popular terminals by environment
-    if env::var("KITTY_WINDOW_ID").is_ok() {
-        return Some("kitty".to_string());
-    }
-    if env::var("ALACRITTY_SOCKET").is_ok() || env::var("ALACRITTY_LOG").is_ok() {
-        return Some("alacritty".to_string());
-    }
-    if env::var("WEZTERM_EXECUTABLE").is_ok() {
-        return Some("wezterm".to_string());
+// Loads nvm from its usual install locations so `nvm` becomes callable
+pub const POSIX_LOAD_NVM: &str = r#"export NVM_DIR="${NVM_DIR:-$HOME/.nvm}"
+if [ -s "$NVM_DIR/nvm.sh" ]; then
+  . "$NVM_DIR/nvm.sh"
+elif [ -s /usr/share/nvm/init-nvm.sh ]; then
+  . /usr/share/nvm/init-nvm.sh
+elif [ -s "$HOME/.config/nvm/nvm.sh" ]; then
+  . "$HOME/.config/nvm/nvm.sh"
+fi
+"#;
+
+// Switches to the version in $_nodash_node: nvm first, then fnm, installing
+// it when missing
+pub const POSIX_USE_NODE: &str = r#"if command -v nvm >/dev/null 2>&1; then
+  nvm use "$_nodash_node" >/dev/null 2>&1 || nvm install "$_nodash_node"
+elif command -v fnm >/dev/null 2>&1; then
+  eval "$(fnm env)"
+  fnm use "$_nodash_node" >/dev/null 2>&1 || fnm install "$_nodash_node"
+elif command -v node >/dev/null 2>&1; then
+  echo 'Node.js available'
+else
+  echo 'No Node.js version manager found'
+fi
+"#;
+
+// Same rules for fish, where nvm is expected to be a fish function
+pub const FISH_USE_NODE: &str = r#"if functions -q nvm
+  nvm use $_nodash_node 2>/dev/null; or nvm install $_nodash_node
+else if command -q fnm
+  fnm use $_nodash_node 2>/dev/null; or fnm install $_nodash_node
+else if command -q node
+  echo 'Node.js available'
+else
+  echo 'No Node.js version manager found'
+end
+"#;
+
+// Activates `spec`, the version the directory asks for; nothing when it asks
+// for none, so nvm never falls back to its usage text
+fn use_node(spec: Option<&str>, fish: bool) -> String {
+    let Some(spec) = spec else {
+        return String::new();
+    };
+    match node::activation(spec, Installed::scan().as_ref()) {
+        Some(version) if fish => format!(
+            "set _nodash_node {}\n{}",
+            quote(&version, true),
+            FISH_USE_NODE
+        ),
+        Some(version) => format!(
+            "_nodash_node={}\n{}",
+            quote(&version, false),
+            POSIX_USE_NODE
+        ),
+        None => format!(
+            "echo {}\n",
+            quote(&format!("No installed Node.js satisfies '{}'", spec), fish)
+        ),
     }
-    if matches!(
-        env::var("TERM_PROGRAM").as_deref(),
-        Ok("Ghostty") | Ok("ghostty")
-    ) {
-        return Some("ghostty".to_string());
+}
+
+// Escape a path for single-quoted shell contexts: ' -> '\'' pattern
+pub fn sh_escape_single_quoted(s: &str) -> String {
+    s.replace('\'', r#"'\''"#)
+}
+
+/// What to start in the new shell once Node is active.
+#[derive(Clone)]
+pub enum Run {
+    /// A program and its arguments, such as `pnpm run dev`.
+    Args(Vec<String>),
+    Profile(Profile),
+}
+
+// Quote a word for the target shell. fish only knows \\ and \' inside
+// single quotes, POSIX shells know no escapes there at all.
+fn quote(word: &str, fish: bool) -> String {
+    if fish {
+        format!("'{}'", word.replace('\\', r"\\").replace('\'', r"\'"))
+    } else {
+        format!("'{}'", sh_escape_single_quoted(word))
     }
+}
 
-    // No tmux/screen/Termux special handling
-    None
+/// Whether `name` can be exported as an environment variable.
+pub fn is_env_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
-fn command_exists(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+fn export_line(key: &str, value: &str, fish: bool) -> String {
+    if fish {
+        format!("set -gx {} {}\n", key, quote(value, true))
+    } else {
+        format!("export {}={}\n", key, quote(value, false))
+    }
 }
 
-// Escape a path for single-quoted shell contexts: ' -> '\'' pattern
-fn sh_escape_single_quoted(s: &str) -> String {
-    s.replace('\'', r#"'\''"#)
+// Shell lines run once Node is active: variables from the manifest's env
+// files and the profile, the manifest's pre-open hooks, then `run`. Variables
+// are exported so they stay set in the interactive shell that follows. An
+// untrusted manifest's env files and hooks are skipped with a note.
+fn startup_lines(proj: &Project, run: Option<&Run>, fish: bool) -> io::Result<String> {
+    let mut lines = String::new();
+    let mut manifest = proj.manifest.clone().unwrap_or_default();
+    let runs_something = !manifest.env_files.is_empty() || !manifest.pre_open.is_empty();
+    if runs_something && !proj.manifest_trusted() {
+        let note = format!(
+            "nodash: skipped the env files and pre_open commands of an untrusted nodash.toml, \
+             see nodash trust {}",
+            proj.name
+        );
+        lines.push_str(&format!("echo {} >&2\n", quote(&note, fish)));
+        manifest.env_files.clear();
+        manifest.pre_open.clear();
+    }
+
+    for file in &manifest.env_files {
+        match read_env_file(&proj.path.join(file)) {
+            Ok(vars) => {
+                for (key, value) in vars {
+                    lines.push_str(&export_line(&key, &value, fish));
+                }
+            }
+            // Optional files such as .env.local are often absent
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("Cannot read env file {}: {}", file, e),
+                ));
+            }
+        }
+    }
+    if let Some(Run::Profile(profile)) = run {
+        for (key, value) in &profile.env {
+            lines.push_str(&export_line(key, value, fish));
+        }
+    }
+
+    for hook in &manifest.pre_open {
+        lines.push_str(hook);
+        lines.push('\n');
+    }
+
+    match run {
+        None => {}
+        Some(Run::Args(args)) => {
+            let words: Vec<String> = args.iter().map(|arg| quote(arg, fish)).collect();
+            lines.push_str(&words.join(" "));
+            lines.push('\n');
+        }
+        Some(Run::Profile(profile)) => {
+            if let Some(command) = &profile.command {
+                lines.push_str(command);
+                lines.push('\n');
+            }
+        }
+    }
+    Ok(lines)
 }
 
 // Create a temporary ZDOTDIR with a .zshrc shim that:
 // 1) sources user's ~/.zshrc
 // 2) cd's into the project
-// 3) initializes nvm/fnm and runs nvm use (or install) last
-fn create_zsh_shim(project_path: &Path) -> io::Result<PathBuf> {
+// 3) initializes nvm/fnm and runs nvm use (or install)
+// 4) runs the startup lines, if any, before handing over to the prompt
+fn create_zsh_shim(project_path: &Path, use_node: &str, startup: &str) -> io::Result<PathBuf> {
     let base = env::temp_dir();
     let unique = format!(
         "nodash-zsh-{}-{}",
@@ -86,47 +217,42 @@ fi
 
     zshrc.push_str(&format!("cd '{}'\n\n", proj));
 
-    zshrc.push_str(
-        r#"export NVM_DIR="${NVM_DIR:-$HOME/.nvm}"
-if [ -s "$NVM_DIR/nvm.sh" ]; then
-  . "$NVM_DIR/nvm.sh"
-elif [ -s /usr/share/nvm/init-nvm.sh ]; then
-  . /usr/share/nvm/init-nvm.sh
-elif [ -s "$HOME/.config/nvm/nvm.sh" ]; then
-  . "$HOME/.config/nvm/nvm.sh"
-fi
-
-if command -v nvm >/dev/null 2>&1; then
-  nvm use >/dev/null 2>&1 || nvm install
-elif command -v fnm >/dev/null 2>&1; then
-  eval "$(fnm env)"
-  fnm use >/dev/null 2>&1 || fnm install
-elif command -v node >/dev/null 2>&1; then
-  echo 'Node.js available'
-else
-  echo 'No Node.js version manager found'
-fi
-
-# Refresh command hash
-hash -r
-"#,
-    );
+    zshrc.push_str(POSIX_LOAD_NVM);
+    zshrc.push('\n');
+    zshrc.push_str(use_node);
+    zshrc.push_str("\n# Refresh command hash\nhash -r\n");
+    zshrc.push('\n');
+    zshrc.push_str(startup);
 
     fs::write(zdotdir.join(".zshrc"), zshrc)?;
     Ok(zdotdir)
 }
 
-pub fn open_project(proj: &mut Project) -> io::Result<()> {
-    proj.last_opened = Some(Utc::now());
-    proj.node_version = detect_node_version(&proj.path).ok();
+// Records the open and builds the project's shell: the directory to start
+// in, Node activation, and the startup lines
+fn prepare(proj: &mut Project, dir: Option<&Path>, run: Option<&Run>) -> io::Result<Launch> {
+    // Spawning a shell would only fail on `cd` there
+    if proj.is_missing() {
+        return Err(proj.missing_error());
+    }
+    proj.record_open();
+    proj.detect_tooling()?;
 
     let shell = get_current_shell();
     let is_fish = is_fish_shell(&shell);
     let is_zsh = is_zsh_shell(&shell);
+    let startup = startup_lines(proj, run, is_fish)?;
+    let cwd = dir.unwrap_or(&proj.path);
+    // A package may pin a version of its own
+    let node_version = match dir {
+        Some(dir) => node::resolve(dir).map(|n| n.version),
+        None => proj.node_version.clone(),
+    };
+    let use_node = use_node(node_version.as_deref(), is_fish);
 
     // Prepare zsh shim if we are launching zsh
     let zdotdir = if is_zsh {
-        Some(create_zsh_shim(&proj.path)?)
+        Some(create_zsh_shim(cwd, &use_node, &startup)?)
     } else {
         None
     };
@@ -134,204 +260,67 @@ pub fn open_project(proj: &mut Project) -> io::Result<()> {
     // Build the command string for non-zsh POSIX shells (bash, sh, etc.)
     // We intentionally DO NOT "exec {shell}" at the end. We start an
     // interactive shell as a child ("{shell} -i") to avoid losing PATH.
+    let proj_path = sh_escape_single_quoted(&cwd.display().to_string());
     let nvm_command = if is_fish {
         // fish branch
-        format!(
-            "cd '{}' ; \
-             if functions -q nvm\n  nvm use 2>/dev/null; or nvm install\n\
-             else if command -q fnm\n  fnm use 2>/dev/null; or fnm install\n\
-             else if command -q node\n  echo 'Node.js available'\n\
-             else\n  echo 'No Node.js version manager found'\n\
-             end ; {}",
-            proj.path.display(),
-            shell
-        )
+        format!("cd '{}'\n{}{}{}", proj_path, use_node, startup, shell)
     } else if !is_zsh {
         // bash/sh branch
         format!(
-            "cd '{}' && \
-             export NVM_DIR=\"${{NVM_DIR:-$HOME/.nvm}}\"; \
-             if [ -s \"$NVM_DIR/nvm.sh\" ]; then \
-               . \"$NVM_DIR/nvm.sh\"; \
-             elif [ -s /usr/share/nvm/init-nvm.sh ]; then \
-               . /usr/share/nvm/init-nvm.sh; \
-             elif [ -s \"$HOME/.config/nvm/nvm.sh\" ]; then \
-               . \"$HOME/.config/nvm/nvm.sh\"; \
-             fi; \
-             if command -v nvm >/dev/null 2>&1; then \
-               (nvm use 2>/dev/null || nvm install); \
-             elif command -v fnm >/dev/null 2>&1; then \
-               eval \"$(fnm env)\" && (fnm use 2>/dev/null || fnm install); \
-             elif command -v node >/dev/null 2>&1; then \
-               echo 'Node.js available'; \
-             else \
-               echo 'No Node.js version manager found'; \
-             fi; \
-             hash -r; \
-             {} -i",
-            proj.path.display(),
-            shell
+            "cd '{}'\n{}{}hash -r\n{}{} -i",
+            proj_path, POSIX_LOAD_NVM, use_node, startup, shell
         )
     } else {
         // zsh is handled via ZDOTDIR shim; we won't pass a "-c" command
         String::new()
     };
 
-    match get_terminal_emulator() {
-        Some(terminal) => match terminal.as_str() {
-            "kitty" => {
-                if !command_exists("kitty") {
-                    return Err(io::Error::new(io::ErrorKind::NotFound, "kitty not found"));
-                }
-                let mut cmd = Command::new("kitty");
-                cmd.arg("--hold").arg("--");
-                // Silence kitty noises, no more pspsps
-                cmd.stdout(Stdio::null()).stderr(Stdio::null());
-
-                if is_zsh {
-                    if let Some(zd) = &zdotdir {
-                        cmd.env("ZDOTDIR", zd);
-                    }
-                    cmd.arg(&shell).arg("-i");
-                } else {
-                    cmd.arg(&shell).arg("-i").arg("-c").arg(&nvm_command);
-                }
-                cmd.spawn()?;
-            }
-            "alacritty" => {
-                if !command_exists("alacritty") {
-                    return Err(io::Error::new(
-                        io::ErrorKind::NotFound,
-                        "alacritty not found",
-                    ));
-                }
-                let mut cmd = Command::new("alacritty");
-                if is_zsh {
-                    if let Some(zd) = &zdotdir {
-                        cmd.env("ZDOTDIR", zd);
-                    }
-                    cmd.arg("-e").arg(&shell).arg("-i");
-                } else {
-                    cmd.arg("-e")
-                        .arg(&shell)
-                        .arg("-i")
-                        .arg("-c")
-                        .arg(&nvm_command);
-                }
-                cmd.spawn()?;
-            }
-            "wezterm" => {
-                if !command_exists("wezterm") {
-                    return Err(io::Error::new(io::ErrorKind::NotFound, "wezterm not found"));
-                }
-                let mut cmd = Command::new("wezterm");
-                cmd.arg("start").arg("--");
-                // Silence wezterm info/warnings
-                cmd.stdout(Stdio::null()).stderr(Stdio::null());
-
-                if is_zsh {
-                    if let Some(zd) = &zdotdir {
-                        cmd.env("ZDOTDIR", zd);
-                    }
-                    cmd.arg(&shell).arg("-i");
-                } else {
-                    cmd.arg(&shell).arg("-i").arg("-c").arg(&nvm_command);
-                }
-                cmd.spawn()?;
-            }
-            "ghostty" => {
-                if !command_exists("ghostty") {
-                    return Err(io::Error::new(io::ErrorKind::NotFound, "ghostty not found"));
-                }
-                let mut cmd = Command::new("ghostty");
-                // Silence ghostty info/warnings
-                cmd.stdout(Stdio::null()).stderr(Stdio::null());
-
-                if is_zsh {
-                    if let Some(zd) = &zdotdir {
-                        cmd.env("ZDOTDIR", zd);
-                    }
-                    // Linux supports -e to run a command
-                    cmd.arg("-e").arg(&shell).arg("-i");
-                } else {
-                    cmd.arg("-e")
-                        .arg(&shell)
-                        .arg("-i")
-                        .arg("-c")
-                        .arg(&nvm_command);
-                }
-                cmd.spawn()?;
-            }
-            _ => {
-                // Unknown detected terminal; try generic "-e"
-                if !command_exists(&terminal) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::NotFound,
-                        format!("{} not found", terminal),
-                    ));
-                }
-                let mut cmd = Command::new(&terminal);
-                // Silence generic terminal noise
-                cmd.stdout(Stdio::null()).stderr(Stdio::null());
-
-                if is_zsh {
-                    if let Some(zd) = &zdotdir {
-                        cmd.env("ZDOTDIR", zd);
-                    }
-                    cmd.arg("-e").arg(&shell).arg("-i");
-                } else {
-                    cmd.arg("-e")
-                        .arg(&shell)
-                        .arg("-i")
-                        .arg("-c")
-                        .arg(&nvm_command);
-                }
-                cmd.spawn()?;
-            }
-        },
-        None => {
-            // Fallback order:
-            // 1) xterm
-            // 2) gnome-terminal
-            // 3) konsole
-            let terminals = [
-                ("xterm", vec!["-e"]),
-                ("gnome-terminal", vec!["--"]),
-                ("konsole", vec!["-e"]),
-            ];
-
-            let mut found = false;
-            for (term, args) in &terminals {
-                if command_exists(term) {
-                    let mut cmd = Command::new(term);
-                    for arg in args {
-                        cmd.arg(arg);
-                    }
-                    // Silence fallback terminal noise
-                    cmd.stdout(Stdio::null()).stderr(Stdio::null());
-
-                    if is_zsh {
-                        if let Some(zd) = &zdotdir {
-                            cmd.env("ZDOTDIR", zd);
-                        }
-                        cmd.arg(&shell).arg("-i");
-                    } else {
-                        cmd.arg(&shell).arg("-i").arg("-c").arg(&nvm_command);
-                    }
-                    cmd.spawn()?;
-                    found = true;
-                    break;
-                }
-            }
-
-            if !found {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "No suitable terminal emulator found",
-                ));
-            }
+    // Packages get their own title, so tmux keeps them apart
+    let title = match dir.and_then(Path::file_name) {
+        Some(package) if cwd != proj.path => {
+            format!("{}/{}", proj.name, package.to_string_lossy())
         }
+        _ => proj.name.clone(),
+    };
+    let mut launch = Launch {
+        shell: vec![shell.clone(), "-i".to_string()],
+        cwd: cwd.to_path_buf(),
+        title,
+        env: Vec::new(),
+        reusable: run.is_none(),
+    };
+    if let Some(zd) = zdotdir {
+        launch.env.push(("ZDOTDIR", zd.into_os_string()));
+    } else {
+        launch.shell.extend(["-c".to_string(), nvm_command]);
     }
+    Ok(launch)
+}
 
-    Ok(())
+/// Opens a new terminal in the project with its Node version active. `run`
+/// is started there once Node is active, such as a `package.json` script or
+/// a launch profile; the shell stays open afterwards so its output can be
+/// read. `dir` starts the shell in a directory inside the project instead of
+/// its root, such as a workspace package.
+pub fn open_project(proj: &mut Project, dir: Option<&Path>, run: Option<&Run>) -> io::Result<()> {
+    let config = Config::load()?;
+    let launch = prepare(proj, dir, run)?;
+    open_terminal(&config, &launch)
+}
+
+/// The same shell as `open_project`, for the current terminal. The caller
+/// saves the project list and leaves the dashboard, then `exec`s it so the
+/// shell takes over nodash's process.
+pub fn shell_here(
+    proj: &mut Project,
+    dir: Option<&Path>,
+    run: Option<&Run>,
+) -> io::Result<Command> {
+    let launch = prepare(proj, dir, run)?;
+    let mut cmd = Command::new(&launch.shell[0]);
+    cmd.args(&launch.shell[1..]).current_dir(&launch.cwd);
+    for (key, value) in &launch.env {
+        cmd.env(key, value);
+    }
+    Ok(cmd)
 }
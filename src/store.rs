@@ -0,0 +1,473 @@
+use chrono::Local;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::project::{HISTORY_LIMIT, Project, SortMode, sort_projects};
+use crate::schema;
+
+const STORE_FILE: &str = "projects.json";
+const LEGACY_STORE_FILE: &str = ".nodash_projects.json";
+// How many previous versions of the store are kept as projects.json.1 .. .N
+const BACKUP_COUNT: usize = 5;
+
+static STORE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Points the store at an explicit file for the rest of the process, as
+/// requested with `--store`.
+pub fn set_store_override(path: PathBuf) {
+    let _ = STORE_OVERRIDE.set(path);
+}
+
+/// Directory holding nodash's own files.
+///
+/// `NODASH_HOME` wins over the platform data directory, which is
+/// `$XDG_DATA_HOME/nodash` (usually `~/.local/share/nodash`) on Linux.
+pub fn data_dir() -> io::Result<PathBuf> {
+    if let Some(home) = env::var_os("NODASH_HOME").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(home));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join("nodash"))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Could not determine a data directory, set NODASH_HOME or pass --store",
+            )
+        })
+}
+
+pub fn store_path() -> io::Result<PathBuf> {
+    if let Some(path) = STORE_OVERRIDE.get() {
+        return Ok(path.clone());
+    }
+    Ok(data_dir()?.join(STORE_FILE))
+}
+
+// Older releases kept the list in ~/.nodash_projects.json. Move it to the
+// default location the first time it is missing there. Explicit overrides
+// are left alone so tests and alternate setups never touch the real file.
+fn migrate_legacy_store(path: &Path) -> io::Result<()> {
+    if STORE_OVERRIDE.get().is_some()
+        || env::var_os("NODASH_HOME").is_some_and(|v| !v.is_empty())
+        || path.exists()
+    {
+        return Ok(());
+    }
+    let Some(legacy) = dirs::home_dir().map(|home| home.join(LEGACY_STORE_FILE)) else {
+        return Ok(());
+    };
+    if !legacy.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Copy first so a failure halfway never loses the only copy
+    fs::copy(&legacy, path)?;
+    fs::remove_file(&legacy)?;
+    eprintln!(
+        "nodash: moved project list from {} to {}",
+        legacy.display(),
+        path.display()
+    );
+    Ok(())
+}
+
+fn with_path(err: io::Error, action: &str, path: &Path) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("Failed to {} {}: {}", action, path.display(), err),
+    )
+}
+
+// Reads the store, salvaging what it can. Problems that were worked around
+// come back as warnings so only `load_projects` reports them.
+fn read_store(file: &Path) -> io::Result<(Vec<Project>, Vec<String>)> {
+    if !file.exists() {
+        return Ok((vec![], vec![]));
+    }
+    let data = fs::read_to_string(file).map_err(|e| with_path(e, "read", file))?;
+
+    let err = match schema::decode(&data) {
+        Ok(decoded) if decoded.skipped.is_empty() => return Ok((decoded.projects, vec![])),
+        Ok(decoded) => {
+            let mut warnings = vec![format!(
+                "skipped {} unreadable project(s) in {}:",
+                decoded.skipped.len(),
+                file.display()
+            )];
+            for entry in &decoded.skipped {
+                warnings.push(format!(
+                    "  #{} {}: {}",
+                    entry.index + 1,
+                    entry.name.as_deref().unwrap_or("(unnamed)"),
+                    entry.reason
+                ));
+            }
+            warnings.push(keep_damaged(file, &data)?);
+            return Ok((decoded.projects, warnings));
+        }
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => return Err(with_path(e, "load", file)),
+        Err(e) => e,
+    };
+
+    // Nothing salvageable, fall back to the newest backup that still parses
+    for backup in (1..=BACKUP_COUNT).map(|n| backup_path(file, n)) {
+        let Ok(backup_data) = fs::read_to_string(&backup) else {
+            continue;
+        };
+        if let Ok(decoded) = schema::decode(&backup_data) {
+            let warnings = vec![
+                format!("could not parse {}: {}", file.display(), err),
+                format!(
+                    "restored {} project(s) from {}",
+                    decoded.projects.len(),
+                    backup.display()
+                ),
+                keep_damaged(file, &data)?,
+            ];
+            return Ok((decoded.projects, warnings));
+        }
+    }
+    Err(with_path(err, "parse", file))
+}
+
+// Copies a damaged store aside before the next save replaces it, unless an
+// identical copy was already kept by an earlier run.
+fn keep_damaged(file: &Path, data: &str) -> io::Result<String> {
+    let prefix = format!(
+        "{}.damaged-",
+        file.file_name().unwrap_or_default().to_string_lossy()
+    );
+    if let Some(dir) = file.parent()
+        && let Ok(entries) = fs::read_dir(dir)
+    {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix)
+                && fs::read_to_string(entry.path()).is_ok_and(|kept| kept == data)
+            {
+                return Ok(format!(
+                    "the original file is kept at {}",
+                    entry.path().display()
+                ));
+            }
+        }
+    }
+
+    let stamp = Local::now().format("%Y%m%d%H%M%S").to_string();
+    let mut copy = sidecar(file, &format!("damaged-{}", stamp));
+    let mut n = 1;
+    while copy.exists() {
+        n += 1;
+        copy = sidecar(file, &format!("damaged-{}-{}", stamp, n));
+    }
+    fs::write(&copy, data).map_err(|e| with_path(e, "write", &copy))?;
+    Ok(format!("the original file is kept at {}", copy.display()))
+}
+
+pub fn load_projects() -> io::Result<Vec<Project>> {
+    let file = store_path()?;
+    migrate_legacy_store(&file).map_err(|e| with_path(e, "migrate project list to", &file))?;
+
+    let (projects, warnings) = read_store(&file)?;
+    for warning in warnings {
+        eprintln!("nodash: {}", warning);
+    }
+    remember_base(&projects);
+
+    // Projects are already sorted when saved, no need to sort again
+    Ok(projects)
+}
+
+/// Saves `projects`, keeping whatever other nodash processes changed in the
+/// store since this one loaded it. `projects` becomes the merged list as
+/// written, sorted by frecency.
+///
+/// The read-merge-write cycle runs under an exclusive lock on a sidecar lock
+/// file, and the new list replaces the old one atomically via rename, so a
+/// crash mid-write leaves the previous list intact.
+pub fn save_projects(projects: &mut Vec<Project>) -> io::Result<()> {
+    let file = store_path()?;
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).map_err(|e| with_path(e, "create", parent))?;
+    }
+
+    let lock_path = sidecar(&file, "lock");
+    let lock = File::create(&lock_path).map_err(|e| with_path(e, "create", &lock_path))?;
+    lock.lock().map_err(|e| with_path(e, "lock", &lock_path))?;
+
+    let (theirs, _) = read_store(&file)?;
+    let base = BASE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default();
+    let mut merged = merge(&base, projects, &theirs);
+
+    // Sort before saving to maintain order
+    sort_projects(&mut merged, SortMode::Frecency);
+
+    let data = schema::encode(&merged)?;
+    rotate_backups(&file).map_err(|e| with_path(e, "back up", &file))?;
+    write_atomic(&file, data.as_bytes()).map_err(|e| with_path(e, "write", &file))?;
+    remember_base(&merged);
+    *projects = merged;
+    Ok(())
+}
+
+// The list as this process last loaded or saved it, the common ancestor for
+// merging with the store on disk
+static BASE: Mutex<Option<Vec<Project>>> = Mutex::new(None);
+
+fn remember_base(projects: &[Project]) {
+    *BASE.lock().unwrap_or_else(|e| e.into_inner()) = Some(projects.to_vec());
+}
+
+fn sidecar(file: &Path, extension: &str) -> PathBuf {
+    let mut name = file.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    file.with_file_name(name)
+}
+
+fn backup_path(file: &Path, n: usize) -> PathBuf {
+    sidecar(file, &n.to_string())
+}
+
+// Shifts projects.json.1 .. .N up by one and copies the current store to .1,
+// dropping the oldest
+fn rotate_backups(file: &Path) -> io::Result<()> {
+    if !file.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(file, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(file, n + 1))?;
+        }
+    }
+    fs::copy(file, backup_path(file, 1))?;
+    Ok(())
+}
+
+fn write_atomic(file: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = sidecar(file, &format!("tmp.{}", std::process::id()));
+    let result = (|| {
+        let mut out = File::create(&tmp)?;
+        out.write_all(data)?;
+        out.sync_all()?;
+        fs::rename(&tmp, file)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+
+    // Persist the rename itself; not every platform can sync a directory
+    if let Some(dir) = file.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Three-way merge of project lists keyed by path.
+///
+/// An entry changed on both sides is merged field by field, see
+/// `merge_project`. Deletions go through unless the other side modified the
+/// entry in the meantime, so no edit is silently dropped.
+fn merge(base: &[Project], ours: &[Project], theirs: &[Project]) -> Vec<Project> {
+    let find = |list: &[Project], path: &Path| list.iter().find(|p| p.path == path).cloned();
+    let mut merged = Vec::new();
+
+    for o in ours {
+        match (find(base, &o.path), find(theirs, &o.path)) {
+            (Some(b), Some(t)) => merged.push(merge_project(&b, o, &t)),
+            // Deleted elsewhere and untouched here
+            (Some(b), None) if *o == b => {}
+            _ => merged.push(o.clone()),
+        }
+    }
+    for t in theirs {
+        if ours.iter().any(|p| p.path == t.path) {
+            continue;
+        }
+        match find(base, &t.path) {
+            // Deleted here and untouched elsewhere
+            Some(b) if *t == b => {}
+            _ => merged.push(t.clone()),
+        }
+    }
+    merged
+}
+
+// Tags and open history are combined and the later `last_opened` kept; any
+// other field takes the side that changed it, ours if both did
+fn merge_project(base: &Project, ours: &Project, theirs: &Project) -> Project {
+    fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> T {
+        if ours == base { theirs } else { ours }.clone()
+    }
+
+    let mut history: Vec<_> = ours
+        .history
+        .iter()
+        .chain(&theirs.history)
+        .copied()
+        .collect();
+    history.sort_unstable();
+    history.dedup();
+    history.drain(..history.len().saturating_sub(HISTORY_LIMIT));
+
+    Project {
+        name: pick(&base.name, &ours.name, &theirs.name),
+        path: ours.path.clone(),
+        last_opened: ours.last_opened.max(theirs.last_opened),
+        node_version: pick(&base.node_version, &ours.node_version, &theirs.node_version),
+        node_source: pick(&base.node_source, &ours.node_source, &theirs.node_source),
+        package_manager: pick(
+            &base.package_manager,
+            &ours.package_manager,
+            &theirs.package_manager,
+        ),
+        tags: merge_tags(&base.tags, &ours.tags, &theirs.tags),
+        history,
+        pinned: pick(&base.pinned, &ours.pinned, &theirs.pinned),
+        profiles: pick(&base.profiles, &ours.profiles, &theirs.profiles),
+        manifest: pick(&base.manifest, &ours.manifest, &theirs.manifest),
+        git: pick(&base.git, &ours.git, &theirs.git),
+        trusted_manifest: pick(
+            &base.trusted_manifest,
+            &ours.trusted_manifest,
+            &theirs.trusted_manifest,
+        ),
+    }
+}
+
+// Tags added on either side, minus those either side removed
+fn merge_tags(base: &[String], ours: &[String], theirs: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = ours
+        .iter()
+        .filter(|t| theirs.contains(t) || !base.contains(t))
+        .cloned()
+        .collect();
+    for t in theirs {
+        if !ours.contains(t) && !base.contains(t) {
+            tags.push(t.clone());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use serde_json::json;
+
+    fn project(name: &str, path: &str) -> Project {
+        serde_json::from_value(json!({ "name": name, "path": path })).unwrap()
+    }
+
+    fn renamed(p: &Project, name: &str) -> Project {
+        Project {
+            name: name.to_string(),
+            ..p.clone()
+        }
+    }
+
+    fn names(list: &[Project]) -> Vec<&str> {
+        let mut names: Vec<&str> = list.iter().map(|p| p.name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn edit_elsewhere_wins_over_untouched_entry() {
+        let base = vec![project("api", "/code/api")];
+        let theirs = vec![renamed(&base[0], "backend")];
+        assert_eq!(names(&merge(&base, &base, &theirs)), ["backend"]);
+    }
+
+    #[test]
+    fn both_sides_edited_keeps_ours() {
+        let base = vec![project("api", "/code/api")];
+        let ours = vec![renamed(&base[0], "ours")];
+        let theirs = vec![renamed(&base[0], "theirs")];
+        assert_eq!(names(&merge(&base, &ours, &theirs)), ["ours"]);
+    }
+
+    #[test]
+    fn both_sides_edited_merges_fields() {
+        let at = |s: i64| DateTime::from_timestamp(s, 0).unwrap();
+        let mut base = project("api", "/code/api");
+        base.tags = vec!["work".to_string(), "old".to_string()];
+        base.history = vec![at(10)];
+        base.last_opened = Some(at(10));
+
+        let mut ours = renamed(&base, "backend");
+        ours.tags = vec!["work".to_string(), "client-a".to_string()];
+        ours.history.push(at(30));
+        ours.last_opened = Some(at(30));
+
+        let mut theirs = base.clone();
+        theirs.pinned = Some(1);
+        theirs.tags.push("node".to_string());
+        theirs.history.push(at(20));
+        theirs.last_opened = Some(at(20));
+
+        let merged = merge(&[base], &[ours], &[theirs]);
+        assert_eq!(merged.len(), 1);
+        let p = &merged[0];
+        assert_eq!(p.name, "backend");
+        assert_eq!(p.pinned, Some(1));
+        assert_eq!(p.tags, ["work", "client-a", "node"]);
+        assert_eq!(p.history, [at(10), at(20), at(30)]);
+        assert_eq!(p.last_opened, Some(at(30)));
+    }
+
+    #[test]
+    fn additions_from_both_sides_are_kept() {
+        let base = vec![project("api", "/code/api")];
+        let mut ours = base.clone();
+        ours.push(project("web", "/code/web"));
+        let mut theirs = base.clone();
+        theirs.push(project("cli", "/code/cli"));
+        assert_eq!(names(&merge(&base, &ours, &theirs)), ["api", "cli", "web"]);
+    }
+
+    #[test]
+    fn deletions_go_through_when_untouched() {
+        let base = vec![project("api", "/code/api"), project("web", "/code/web")];
+        let ours = vec![base[0].clone()];
+        let theirs = vec![base[1].clone()];
+        assert!(merge(&base, &ours, &theirs).is_empty());
+    }
+
+    #[test]
+    fn deletion_loses_against_an_edit() {
+        let base = vec![project("api", "/code/api")];
+        let edited = vec![renamed(&base[0], "backend")];
+        assert_eq!(names(&merge(&base, &[], &edited)), ["backend"]);
+        assert_eq!(names(&merge(&base, &edited, &[])), ["backend"]);
+    }
+
+    #[test]
+    fn relocation_replaces_the_old_path() {
+        let base = vec![project("api", "/code/api")];
+        let moved = vec![Project {
+            path: PathBuf::from("/work/api"),
+            ..base[0].clone()
+        }];
+
+        let merged = merge(&base, &moved, &base);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].path, Path::new("/work/api"));
+
+        let merged = merge(&base, &base, &moved);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].path, Path::new("/work/api"));
+    }
+}
@@ -0,0 +1,335 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::config::{Config, TmuxMode, ZellijMode};
+use crate::tmux::Tmux;
+use crate::zellij::Zellij;
+
+/// The shell to start in a new terminal, with everything it needs.
+pub struct Launch {
+    /// The shell and its arguments, e.g. `bash -i -c <script>`.
+    pub shell: Vec<String>,
+    pub cwd: PathBuf,
+    /// Window title, the project name.
+    pub title: String,
+    /// Extra environment for the shell, such as zsh's `ZDOTDIR`.
+    pub env: Vec<(&'static str, OsString)>,
+    /// Nothing is to be run, so an already open shell in `cwd` will do.
+    pub reusable: bool,
+}
+
+impl Launch {
+    // The shell with the terminal's own arguments in front
+    fn command(&self, program: &str, args: &[String]) -> Command {
+        let mut cmd = Command::new(program);
+        cmd.args(args).current_dir(&self.cwd);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        cmd
+    }
+
+    // The extra environment as KEY=VALUE, for terminals started remotely
+    fn env_pairs(&self) -> Vec<OsString> {
+        self.env
+            .iter()
+            .map(|(key, value)| {
+                let mut pair = OsString::from(format!("{}=", key));
+                pair.push(value);
+                pair
+            })
+            .collect()
+    }
+}
+
+/// A way to open a new terminal window running a shell.
+pub trait TerminalLauncher {
+    fn name(&self) -> &str;
+    /// Whether the terminal is installed.
+    fn is_available(&self) -> bool;
+    fn launch(&self, launch: &Launch) -> io::Result<()>;
+}
+
+/// A terminal that takes the command to run after some fixed arguments,
+/// like `kitty --hold -- <shell>` or `xterm -e <shell>`.
+struct Builtin {
+    program: String,
+    args: &'static [&'static str],
+    /// Discard the terminal's own output, which would clutter the dashboard.
+    quiet: bool,
+}
+
+impl TerminalLauncher for Builtin {
+    fn name(&self) -> &str {
+        &self.program
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists(&self.program)
+    }
+
+    fn launch(&self, launch: &Launch) -> io::Result<()> {
+        let mut args: Vec<String> = self.args.iter().map(|a| a.to_string()).collect();
+        args.extend(launch.shell.iter().cloned());
+        let mut cmd = launch.command(&self.program, &args);
+        if self.quiet {
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+        cmd.spawn()?;
+        Ok(())
+    }
+}
+
+/// kitty or WezTerm: a new tab in the instance nodash runs in, through
+/// their remote control, or a new window when that is not possible.
+struct Tabbed {
+    window: Builtin,
+}
+
+impl Tabbed {
+    // Whether the tab was opened; any failure leaves it to a new window
+    fn open_tab(&self, launch: &Launch) -> bool {
+        let cwd = launch.cwd.display().to_string();
+        match self.window.program.as_str() {
+            // KITTY_LISTEN_ON is set when remote control goes through a
+            // socket, otherwise kitty @ talks to the terminal it runs in
+            "kitty"
+                if env::var_os("KITTY_WINDOW_ID").is_some()
+                    || env::var_os("KITTY_LISTEN_ON").is_some() =>
+            {
+                let mut cmd = Command::new("kitty");
+                cmd.args(["@", "launch", "--type=tab", "--cwd", &cwd])
+                    .args(["--tab-title", &launch.title]);
+                for pair in launch.env_pairs() {
+                    cmd.arg("--env").arg(pair);
+                }
+                cmd.args(&launch.shell);
+                succeeds(cmd)
+            }
+            "wezterm" if env::var_os("WEZTERM_PANE").is_some() => {
+                // `cli spawn` cannot set variables, so `env` does it
+                let mut cmd = Command::new("wezterm");
+                cmd.args(["cli", "spawn", "--cwd", &cwd, "--"]);
+                if !launch.env.is_empty() {
+                    cmd.arg("env").args(launch.env_pairs());
+                }
+                cmd.args(&launch.shell);
+                let Ok(output) = cmd.stdin(Stdio::null()).stderr(Stdio::null()).output() else {
+                    return false;
+                };
+                if !output.status.success() {
+                    return false;
+                }
+                // spawn prints the new pane's id, which names its tab
+                let pane = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                let mut title = Command::new("wezterm");
+                title.args(["cli", "set-tab-title", "--pane-id", &pane, &launch.title]);
+                succeeds(title);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Runs a remote control command quietly and reports whether it worked
+fn succeeds(mut cmd: Command) -> bool {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+impl TerminalLauncher for Tabbed {
+    fn name(&self) -> &str {
+        self.window.name()
+    }
+
+    fn is_available(&self) -> bool {
+        self.window.is_available()
+    }
+
+    fn launch(&self, launch: &Launch) -> io::Result<()> {
+        if self.open_tab(launch) {
+            return Ok(());
+        }
+        self.window.launch(launch)
+    }
+}
+
+/// Built-in support for `name`; unknown terminals get the common `-e`.
+fn builtin(name: &str) -> Builtin {
+    let (args, quiet): (&'static [&'static str], bool) = match name {
+        "kitty" => (&["--hold", "--"], true),
+        "alacritty" => (&["-e"], false),
+        "wezterm" => (&["start", "--"], true),
+        "gnome-terminal" => (&["--"], true),
+        _ => (&["-e"], true),
+    };
+    Builtin {
+        program: name.to_string(),
+        args,
+        quiet,
+    }
+}
+
+/// A terminal defined in `config.toml` as a command template.
+///
+/// `{cwd}` and `{title}` are replaced inside any argument. An argument that
+/// is exactly `{shell}` becomes the shell and its arguments; without one,
+/// they are appended.
+struct Template {
+    name: String,
+    command: Vec<String>,
+}
+
+impl TerminalLauncher for Template {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists(&self.command[0])
+    }
+
+    fn launch(&self, launch: &Launch) -> io::Result<()> {
+        let cwd = launch.cwd.display().to_string();
+        let mut args = Vec::new();
+        let mut has_shell = false;
+        for arg in &self.command[1..] {
+            if arg == "{shell}" {
+                args.extend(launch.shell.iter().cloned());
+                has_shell = true;
+            } else {
+                args.push(arg.replace("{cwd}", &cwd).replace("{title}", &launch.title));
+            }
+        }
+        if !has_shell {
+            args.extend(launch.shell.iter().cloned());
+        }
+        launch
+            .command(&self.command[0], &args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+}
+
+fn get_terminal_emulator() -> Option<String> {
+    // Detect some popular terminals by environment
+    if env::var("KITTY_WINDOW_ID").is_ok() {
+        return Some("kitty".to_string());
+    }
+    if env::var("ALACRITTY_SOCKET").is_ok() || env::var("ALACRITTY_LOG").is_ok() {
+        return Some("alacritty".to_string());
+    }
+    if env::var("WEZTERM_EXECUTABLE").is_ok() {
+        return Some("wezterm".to_string());
+    }
+    if matches!(
+        env::var("TERM_PROGRAM").as_deref(),
+        Ok("Ghostty") | Ok("ghostty")
+    ) {
+        return Some("ghostty".to_string());
+    }
+
+    // tmux and Zellij run inside one of these, see `launchers`; no
+    // screen/Termux special handling
+    None
+}
+
+/// Runs a multiplexer command such as `tmux new-window`, returning its
+/// trimmed output, or its error output as the error.
+pub fn run_quiet(cmd: &mut Command) -> io::Result<String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let output = cmd.stdin(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{}: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn command_exists(cmd: &str) -> bool {
+    Command::new("which")
+        .arg(cmd)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+// Every terminal worth trying, best first: tmux or Zellij when nodash runs
+// inside them, the user's preference order, the terminal nodash runs in,
+// then common fallbacks. Templates from the config replace the built-in
+// support for a terminal of the same name, including opening tabs.
+fn launchers(config: &Config) -> Vec<Box<dyn TerminalLauncher>> {
+    let mut names: Vec<String> = config.terminals.clone();
+    names.extend(get_terminal_emulator());
+    names.extend(
+        ["xterm", "gnome-terminal", "konsole"]
+            .iter()
+            .map(|name| name.to_string()),
+    );
+
+    let mut seen = Vec::new();
+    let mut launchers: Vec<Box<dyn TerminalLauncher>> = Vec::new();
+    let inside = |var: &str| env::var_os(var).is_some_and(|v| !v.is_empty());
+    if inside("TMUX") && config.tmux.mode != TmuxMode::Off {
+        launchers.push(Box::new(Tmux {
+            mode: config.tmux.mode,
+            reuse: config.tmux.reuse,
+        }));
+    }
+    if inside("ZELLIJ") && config.zellij.mode != ZellijMode::Off {
+        launchers.push(Box::new(Zellij {
+            mode: config.zellij.mode,
+            layouts: config.zellij.layouts,
+        }));
+    }
+    for name in names {
+        if seen.contains(&name) {
+            continue;
+        }
+        match config.terminal.get(&name) {
+            Some(template) => launchers.push(Box::new(Template {
+                name: name.clone(),
+                command: template.command.clone(),
+            })),
+            None if config.tabs && matches!(name.as_str(), "kitty" | "wezterm") => {
+                launchers.push(Box::new(Tabbed {
+                    window: builtin(&name),
+                }))
+            }
+            None => launchers.push(Box::new(builtin(&name))),
+        }
+        seen.push(name);
+    }
+    launchers
+}
+
+/// Opens `launch` in the first available terminal.
+pub fn open_terminal(config: &Config, launch: &Launch) -> io::Result<()> {
+    let launchers = launchers(config);
+    let launcher = launchers
+        .iter()
+        .find(|launcher| launcher.is_available())
+        .ok_or_else(|| {
+            let tried: Vec<&str> = launchers.iter().map(|l| l.name()).collect();
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "No suitable terminal emulator found (tried {}); use --here or h in the dashboard to open in this terminal",
+                    tried.join(", ")
+                ),
+            )
+        })?;
+    launcher.launch(launch)
+}
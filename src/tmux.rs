@@ -0,0 +1,115 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::config::TmuxMode;
+use crate::terminal::{Launch, TerminalLauncher, command_exists, run_quiet};
+
+// Window option recording the directory a window was opened for, which
+// survives renaming the window
+const DIR_OPTION: &str = "@nodash_dir";
+
+/// Opens projects in the tmux server nodash runs under, as new windows of
+/// the current session or as sessions of their own.
+pub struct Tmux {
+    pub mode: TmuxMode,
+    /// Switch to the project's existing window or session instead of opening
+    /// another one, unless something is to be run.
+    pub reuse: bool,
+}
+
+fn tmux() -> Command {
+    Command::new("tmux")
+}
+
+// `new-window` and `new-session` run the shell with the launch's variables
+fn shell_args(cmd: &mut Command, launch: &Launch) {
+    for (key, value) in &launch.env {
+        cmd.arg("-e")
+            .arg(format!("{}={}", key, value.to_string_lossy()));
+    }
+    cmd.args(&launch.shell);
+}
+
+// tmux reserves `.` and `:` in target names
+fn session_name(title: &str) -> String {
+    title.replace(['.', ':'], "_")
+}
+
+fn has_session(name: &str) -> bool {
+    tmux()
+        .args(["has-session", "-t", &format!("={}", name)])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+impl Tmux {
+    fn open_window(&self, launch: &Launch) -> io::Result<()> {
+        let cwd = launch.cwd.display().to_string();
+        if self.reuse && launch.reusable {
+            let format = format!("#{{window_id}}\t#{{{}}}", DIR_OPTION);
+            let windows = run_quiet(tmux().args(["list-windows", "-F", &format]))?;
+            let existing = windows
+                .lines()
+                .filter_map(|line| line.split_once('\t'))
+                .find(|(_, dir)| *dir == cwd);
+            if let Some((id, _)) = existing {
+                run_quiet(tmux().args(["select-window", "-t", id]))?;
+                return Ok(());
+            }
+        }
+
+        let mut cmd = tmux();
+        cmd.args(["new-window", "-P", "-F", "#{window_id}"]).args([
+            "-n",
+            &launch.title,
+            "-c",
+            &cwd,
+        ]);
+        shell_args(&mut cmd, launch);
+        let id = run_quiet(&mut cmd)?;
+        // Fails when the shell has already exited, which it reports itself
+        let _ = run_quiet(tmux().args(["set-option", "-w", "-t", &id, DIR_OPTION, &cwd]));
+        Ok(())
+    }
+
+    fn open_session(&self, launch: &Launch) -> io::Result<()> {
+        let base = session_name(&launch.title);
+        let mut name = base.clone();
+        if has_session(&name) && !(self.reuse && launch.reusable) {
+            let mut n = 2;
+            while has_session(&format!("{}-{}", base, n)) {
+                n += 1;
+            }
+            name = format!("{}-{}", base, n);
+        }
+
+        if !has_session(&name) {
+            let mut cmd = tmux();
+            cmd.args(["new-session", "-d", "-s", &name, "-c"])
+                .arg(&launch.cwd);
+            shell_args(&mut cmd, launch);
+            run_quiet(&mut cmd)?;
+        }
+        run_quiet(tmux().args(["switch-client", "-t", &format!("={}", name)]))?;
+        Ok(())
+    }
+}
+
+impl TerminalLauncher for Tmux {
+    fn name(&self) -> &str {
+        "tmux"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("tmux")
+    }
+
+    fn launch(&self, launch: &Launch) -> io::Result<()> {
+        match self.mode {
+            TmuxMode::Session => self.open_session(launch),
+            _ => self.open_window(launch),
+        }
+    }
+}
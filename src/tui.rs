@@ -6,10 +6,15 @@ use ratatui::layout::{Alignment, Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use std::collections::{HashMap, HashSet};
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::project::{Project, detect_node_version, save_projects};
+use crate::node::{self, Installed, Resolution};
+use crate::package::{self, PackageManager, WorkspacePackage};
+use crate::project::{Project, SortMode, add_project, pin_project, sort_projects, unpin_project};
+use crate::shell::Run;
+use crate::store::save_projects;
 
 const HIGHLIGHT_COLOR: Color = Color::LightCyan;
 const ACCENT_COLOR: Color = Color::LightGreen;
@@ -17,12 +22,454 @@ const TEXT_COLOR: Color = Color::White;
 const MUTED_COLOR: Color = Color::DarkGray;
 const ERROR_COLOR: Color = Color::Red;
 
-pub fn run_app<F>(projects: &mut Vec<Project>, mut open_cb: F) -> io::Result<()>
+/// What the dashboard does once a project has been handed to the open callback.
+pub enum AfterOpen {
+    Stay,
+    Exit,
+}
+
+/// Where the open callback should start the project's shell.
+#[derive(Clone, Copy, PartialEq)]
+pub enum OpenIn {
+    NewTerminal,
+    /// In place of the dashboard, once it has been torn down.
+    Here,
+}
+
+/// How the dashboard starts out.
+pub struct AppOptions {
+    /// Pre-filled search; non-empty starts in search mode.
+    pub query: String,
+    pub sort: SortMode,
+    /// Offer the scripts panel; `pick` only selects a project.
+    pub scripts: bool,
+    /// Offer opening in the current terminal.
+    pub here: bool,
+}
+
+/// Things to run in one project, shown in place of the project list.
+struct ScriptsPanel {
+    project: usize,
+    /// Workspace package whose scripts are listed, instead of the root's.
+    package: Option<WorkspacePackage>,
+    /// Name, description and what to run: launch profiles, then dependency
+    /// install and the `package.json` scripts.
+    entries: Vec<(String, String, Run)>,
+    /// Why there is nothing to run, e.g. a missing `package.json`.
+    error: Option<String>,
+    selected: usize,
+}
+
+impl ScriptsPanel {
+    fn new(project: usize, p: &Project, package: Option<WorkspacePackage>) -> ScriptsPanel {
+        let manager = p.package_manager.unwrap_or(PackageManager::Npm);
+        let dir = package
+            .as_ref()
+            .map_or(p.path.clone(), |pkg| pkg.path.clone());
+        let mut panel = ScriptsPanel {
+            project,
+            package,
+            entries: Vec::new(),
+            error: None,
+            selected: 0,
+        };
+        // Profiles belong to the repository root
+        if panel.package.is_none() {
+            panel
+                .entries
+                .extend(p.all_profiles().into_iter().map(|profile| {
+                    (
+                        profile.name.clone(),
+                        profile.describe(),
+                        Run::Profile(profile),
+                    )
+                }));
+        }
+        match package::scripts(&dir) {
+            Ok(scripts) => {
+                let install = manager.install_args();
+                panel
+                    .entries
+                    .push(("install".to_string(), install.join(" "), Run::Args(install)));
+                panel
+                    .entries
+                    .extend(scripts.into_iter().map(|(name, command)| {
+                        let args = manager.run_args(&name);
+                        (name, command, Run::Args(args))
+                    }));
+            }
+            Err(e) if panel.entries.is_empty() => {
+                panel.error = Some(format!("Cannot read package.json: {}", e));
+            }
+            Err(_) => {}
+        }
+        panel
+    }
+}
+
+fn script_line<'a>(name: &'a str, description: &'a str, run: &Run) -> Line<'a> {
+    // Profiles stand out from plain scripts
+    let color = match run {
+        Run::Profile(_) => ACCENT_COLOR,
+        Run::Args(_) => TEXT_COLOR,
+    };
+    Line::from(vec![
+        Span::styled(
+            name,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("  {}", description),
+            Style::default().fg(MUTED_COLOR),
+        ),
+    ])
+}
+
+/// A line of the project list.
+enum Row {
+    /// Header of a tag group; `None` collects untagged projects.
+    Group {
+        tag: Option<String>,
+        count: usize,
+        folded: bool,
+    },
+    /// Section title, skipped when navigating.
+    Heading(&'static str),
+    Project(usize),
+    /// A package of an expanded workspace, by project and package index.
+    Package {
+        project: usize,
+        package: usize,
+    },
+}
+
+impl Row {
+    fn selectable(&self) -> bool {
+        !matches!(self, Row::Heading(_))
+    }
+}
+
+// Index of the nearest selectable row from `from` in the given direction,
+// or `from` itself when there is none
+fn step(rows: &[Row], from: usize, forward: bool) -> usize {
+    let found = if forward {
+        (from + 1..rows.len()).find(|&i| rows[i].selectable())
+    } else {
+        (0..from).rev().find(|&i| rows[i].selectable())
+    };
+    found.unwrap_or(from)
+}
+
+// Puts the packages of expanded workspaces under their project rows
+fn add_packages(
+    rows: Vec<Row>,
+    projects: &[Project],
+    workspaces: &HashMap<PathBuf, Vec<WorkspacePackage>>,
+    expanded: &HashSet<PathBuf>,
+) -> Vec<Row> {
+    let mut out = Vec::with_capacity(rows.len());
+    for row in rows {
+        let packages = match row {
+            Row::Project(idx) if expanded.contains(&projects[idx].path) => workspaces
+                .get(&projects[idx].path)
+                .map(|packages| (idx, packages.len())),
+            _ => None,
+        };
+        out.push(row);
+        if let Some((project, count)) = packages {
+            out.extend((0..count).map(|package| Row::Package { project, package }));
+        }
+    }
+    out
+}
+
+// Lays out the filtered projects, either flat or folded under their tags.
+// A project with several tags shows up in each of its groups.
+fn build_rows(
+    projects: &[Project],
+    filtered: &[usize],
+    grouped: bool,
+    folded: &HashSet<Option<String>>,
+) -> Vec<Row> {
+    if !grouped {
+        let (pinned, rest): (Vec<usize>, Vec<usize>) = filtered
+            .iter()
+            .partition(|&&idx| projects[idx].pinned.is_some());
+        if pinned.is_empty() {
+            return rest.into_iter().map(Row::Project).collect();
+        }
+
+        let mut rows = vec![Row::Heading("Pinned")];
+        rows.extend(pinned.into_iter().map(Row::Project));
+        if !rest.is_empty() {
+            rows.push(Row::Heading("Projects"));
+            rows.extend(rest.into_iter().map(Row::Project));
+        }
+        return rows;
+    }
+
+    let mut tags: Vec<&str> = filtered
+        .iter()
+        .flat_map(|&idx| projects[idx].all_tags())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    let mut groups: Vec<(Option<String>, Vec<usize>)> = tags
+        .into_iter()
+        .map(|tag| {
+            let members = filtered
+                .iter()
+                .copied()
+                .filter(|&idx| projects[idx].all_tags().contains(&tag))
+                .collect();
+            (Some(tag.to_string()), members)
+        })
+        .collect();
+    let untagged: Vec<usize> = filtered
+        .iter()
+        .copied()
+        .filter(|&idx| projects[idx].all_tags().is_empty())
+        .collect();
+    if !untagged.is_empty() {
+        groups.push((None, untagged));
+    }
+
+    let mut rows = Vec::new();
+    for (tag, members) in groups {
+        let is_folded = folded.contains(&tag);
+        rows.push(Row::Group {
+            count: members.len(),
+            folded: is_folded,
+            tag,
+        });
+        if !is_folded {
+            rows.extend(members.into_iter().map(Row::Project));
+        }
+    }
+    rows
+}
+
+fn project_line<'a>(
+    idx: usize,
+    p: &'a Project,
+    indent: bool,
+    installed: Option<&Installed>,
+    workspace: Option<(usize, bool)>,
+    missing: bool,
+) -> Line<'a> {
+    let mut spans = vec![
+        Span::raw(if indent { "  " } else { "" }),
+        Span::styled(format!("{}. ", idx + 1), Style::default().fg(MUTED_COLOR)),
+        Span::styled(
+            &p.name,
+            Style::default().fg(TEXT_COLOR).add_modifier(Modifier::BOLD),
+        ),
+    ];
+
+    if p.pinned.is_some() {
+        spans.push(Span::raw(" 📌"));
+    }
+
+    if missing {
+        spans.push(Span::styled(
+            " ✗ missing",
+            Style::default()
+                .fg(ERROR_COLOR)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    // Add node version
+    if let Some(ref version) = p.node_version {
+        spans.push(Span::styled(" (Node ", Style::default().fg(MUTED_COLOR)));
+        match installed.map(|i| i.resolve(version)) {
+            Some(Resolution::Installed(v)) => {
+                spans.push(Span::styled(
+                    node::describe(version, v),
+                    Style::default().fg(ACCENT_COLOR),
+                ));
+            }
+            Some(Resolution::Missing) => {
+                spans.push(Span::styled(version, Style::default().fg(ERROR_COLOR)));
+                spans.push(Span::styled(
+                    " not installed",
+                    Style::default().fg(ERROR_COLOR),
+                ));
+            }
+            _ => spans.push(Span::styled(version, Style::default().fg(ACCENT_COLOR))),
+        }
+        if let Some(ref source) = p.node_source {
+            spans.push(Span::styled(
+                format!(" · {}", source),
+                Style::default().fg(MUTED_COLOR),
+            ));
+        }
+        spans.push(Span::styled(")", Style::default().fg(MUTED_COLOR)));
+    }
+
+    if let Some(manager) = p.package_manager {
+        spans.push(Span::styled(
+            format!(" [{}]", manager),
+            Style::default().fg(TEXT_COLOR),
+        ));
+    }
+
+    if let Some((count, expanded)) = workspace {
+        let marker = if expanded { "▾" } else { "▸" };
+        spans.push(Span::styled(
+            format!(" {} {} packages", marker, count),
+            Style::default().fg(HIGHLIGHT_COLOR),
+        ));
+    }
+
+    // Add last opened date
+    if let Some(ts) = p.last_opened {
+        spans.push(Span::styled(" - ", Style::default().fg(MUTED_COLOR)));
+        spans.push(Span::styled(
+            ts.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            Style::default().fg(MUTED_COLOR),
+        ));
+    }
+
+    // Add tags
+    for tag in p.all_tags() {
+        spans.push(Span::styled(
+            format!(" #{}", tag),
+            Style::default().fg(HIGHLIGHT_COLOR),
+        ));
+    }
+
+    Line::from(spans)
+}
+
+fn package_line<'a>(package: &'a WorkspacePackage, root: &Path, indent: bool) -> Line<'a> {
+    let location = package
+        .path
+        .strip_prefix(root)
+        .unwrap_or(&package.path)
+        .display()
+        .to_string();
+    Line::from(vec![
+        Span::raw(if indent { "      " } else { "    " }),
+        Span::styled("↳ ", Style::default().fg(MUTED_COLOR)),
+        Span::styled(&package.name, Style::default().fg(TEXT_COLOR)),
+        Span::styled(format!("  {}", location), Style::default().fg(MUTED_COLOR)),
+    ])
+}
+
+fn group_line(tag: &Option<String>, count: usize, folded: bool) -> Line<'static> {
+    let marker = if folded { "▸ " } else { "▾ " };
+    let label = match tag {
+        Some(tag) => format!("#{}", tag),
+        None => "untagged".to_string(),
+    };
+    Line::from(vec![
+        Span::styled(marker, Style::default().fg(MUTED_COLOR)),
+        Span::styled(
+            label,
+            Style::default()
+                .fg(ACCENT_COLOR)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!(" ({})", count), Style::default().fg(MUTED_COLOR)),
+    ])
+}
+
+fn heading_line(title: &'static str) -> Line<'static> {
+    Line::from(Span::styled(
+        title,
+        Style::default()
+            .fg(MUTED_COLOR)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+    ))
+}
+
+fn footer_line(hints: &[(&'static str, &'static str, Color)]) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (i, (key, label, color)) in hints.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" | "));
+        }
+        spans.push(Span::styled(
+            *key,
+            Style::default().fg(*color).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(
+            format!(" {}", label),
+            Style::default().fg(TEXT_COLOR),
+        ));
+    }
+    Line::from(spans)
+}
+
+// Paths of the projects whose directory is gone
+fn missing_paths(projects: &[Project]) -> HashSet<PathBuf> {
+    projects
+        .iter()
+        .filter(|p| p.is_missing())
+        .map(|p| p.path.clone())
+        .collect()
+}
+
+// Saves an open, which counts towards frecency even when it failed, and
+// re-reads what it may have changed: a new terminal may have installed the
+// project's Node version, and a failed `cd` reveals a missing directory
+fn save_open(
+    projects: &mut Vec<Project>,
+    sort: SortMode,
+    installed: &mut Option<Installed>,
+    missing: &mut HashSet<PathBuf>,
+) -> io::Result<()> {
+    save_projects(projects)?;
+    *installed = Installed::scan();
+    *missing = missing_paths(projects);
+    sort_projects(projects, sort);
+    Ok(())
+}
+
+type Term<W> = Terminal<CrosstermBackend<W>>;
+
+// Leaves the alternate screen so a plain line-based prompt can run
+fn suspend<W: Write>(terminal: &mut Term<W>) -> io::Result<()> {
+    terminal::disable_raw_mode()?;
+    execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+fn resume<W: Write>(terminal: &mut Term<W>) -> io::Result<()> {
+    terminal.clear()?;
+    execute!(terminal.backend_mut(), terminal::EnterAlternateScreen)?;
+    terminal::enable_raw_mode()?;
+    Ok(())
+}
+
+fn prompt<W: Write>(terminal: &mut Term<W>, label: &str) -> io::Result<String> {
+    write!(terminal.backend_mut(), "{}", label)?;
+    terminal.backend_mut().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Runs the dashboard, drawing to `out`.
+///
+/// Passing stderr instead of stdout keeps stdout free for the caller, which is
+/// what `nodash pick` relies on to print the chosen path.
+pub fn run_app<W, F>(
+    out: W,
+    projects: &mut Vec<Project>,
+    options: AppOptions,
+    mut open_cb: F,
+) -> io::Result<()>
 where
-    F: FnMut(&mut Project) -> io::Result<()>,
+    W: Write,
+    F: FnMut(&mut Project, Option<&Path>, Option<&Run>, OpenIn) -> io::Result<AfterOpen>,
 {
-    let stdout = io::stdout();
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(out);
     let mut terminal = Terminal::new(backend)?;
 
     terminal::enable_raw_mode()?;
@@ -33,31 +480,67 @@ where
     let mut list_state = ListState::default();
     list_state.select(Some(selected));
 
-    let mut search_query = String::new();
-    let mut search_mode = false;
+    let mut search_query = options.query;
+    let mut search_mode = !search_query.is_empty();
+
+    let mut sort = options.sort;
+    sort_projects(projects, sort);
+
+    let mut grouped = false;
+    let mut follow: Option<PathBuf> = None;
+    let mut folded: HashSet<Option<String>> = HashSet::new();
+    let mut installed = Installed::scan();
+    // Monorepos, read once; expanding one lists its packages
+    let mut workspaces: HashMap<PathBuf, Vec<WorkspacePackage>> = HashMap::new();
+    for p in projects.iter() {
+        let packages = package::workspace_packages(&p.path);
+        if !packages.is_empty() {
+            workspaces.insert(p.path.clone(), packages);
+        }
+    }
+    let mut expanded: HashSet<PathBuf> = HashSet::new();
+    let mut missing = missing_paths(projects);
+    // Shown in place of the key hints until the next key press
+    let mut status: Option<String> = None;
+    let mut panel: Option<ScriptsPanel> = None;
+    let mut panel_state = ListState::default();
 
     loop {
         let filtered_indices: Vec<usize> = projects
             .iter()
             .enumerate()
-            .filter(|(_, p)| {
-                if search_query.is_empty() {
-                    true
-                } else {
-                    p.name.to_lowercase().contains(&search_query.to_lowercase())
-                        || p.path
-                            .to_string_lossy()
-                            .to_lowercase()
-                            .contains(&search_query.to_lowercase())
-                }
-            })
+            .filter(|(_, p)| p.matches_query(&search_query))
             .map(|(idx, _)| idx)
             .collect();
+        let rows = add_packages(
+            build_rows(projects, &filtered_indices, grouped, &folded),
+            projects,
+            &workspaces,
+            &expanded,
+        );
 
-        if selected >= filtered_indices.len() && !filtered_indices.is_empty() {
-            selected = filtered_indices.len() - 1;
+        // Keep the cursor on a project or package that just moved in the list
+        if let Some(path) = follow.take()
+            && let Some(row) = rows.iter().position(|row| match row {
+                Row::Project(idx) => projects[*idx].path == path,
+                Row::Package { project, package } => {
+                    workspaces[&projects[*project].path][*package].path == path
+                }
+                _ => false,
+            })
+        {
+            selected = row;
+        }
+        if selected >= rows.len() && !rows.is_empty() {
+            selected = rows.len() - 1;
         }
-        list_state.select(if filtered_indices.is_empty() {
+        if rows.get(selected).is_some_and(|row| !row.selectable()) {
+            selected = step(&rows, selected, true);
+            if !rows[selected].selectable() {
+                selected = step(&rows, selected, false);
+            }
+        }
+        list_state.select(if rows.is_empty() {
             None
         } else {
             Some(selected)
@@ -96,7 +579,7 @@ where
             // Search input (only if in search mode)
             if search_mode {
                 let search_text = if search_query.is_empty() {
-                    "Type to search projects...".to_string()
+                    "Type to search projects... (tag:NAME filters by tag)".to_string()
                 } else {
                     search_query.clone()
                 };
@@ -125,7 +608,7 @@ where
             }
 
             // Project list
-            let items: Vec<ListItem> = if filtered_indices.is_empty() {
+            let items: Vec<ListItem> = if rows.is_empty() {
                 vec![ListItem::new(Line::from(vec![Span::styled(
                     "No projects found.",
                     Style::default()
@@ -133,53 +616,50 @@ where
                         .add_modifier(Modifier::ITALIC),
                 )]))]
             } else {
-                filtered_indices
-                    .iter()
-                    .filter_map(|&idx| projects.get(idx))
-                    .enumerate()
-                    .map(|(display_idx, p)| {
-                        let original_idx = filtered_indices[display_idx]; // Keep original index for display numbering
-
-                        let mut spans = vec![
-                            Span::styled(
-                                format!("{}. ", original_idx + 1),
-                                Style::default().fg(MUTED_COLOR),
-                            ),
-                            Span::styled(
-                                &p.name,
-                                Style::default().fg(TEXT_COLOR).add_modifier(Modifier::BOLD),
-                            ),
-                        ];
-
-                        // Add node version
-                        if let Some(ref version) = p.node_version {
-                            spans.push(Span::styled(" (Node ", Style::default().fg(MUTED_COLOR)));
-                            spans.push(Span::styled(version, Style::default().fg(ACCENT_COLOR)));
-                            spans.push(Span::styled(")", Style::default().fg(MUTED_COLOR)));
+                rows.iter()
+                    .map(|row| match row {
+                        Row::Group { tag, count, folded } => {
+                            ListItem::new(group_line(tag, *count, *folded))
                         }
-
-                        // Add last opened date
-                        if let Some(ts) = p.last_opened {
-                            spans.push(Span::styled(" - ", Style::default().fg(MUTED_COLOR)));
-                            spans.push(Span::styled(
-                                ts.with_timezone(&chrono::Local)
-                                    .format("%Y-%m-%d %H:%M")
-                                    .to_string(),
-                                Style::default().fg(MUTED_COLOR),
-                            ));
+                        Row::Heading(title) => ListItem::new(heading_line(title)),
+                        Row::Project(idx) => {
+                            let path = &projects[*idx].path;
+                            let workspace = workspaces
+                                .get(path)
+                                .map(|packages| (packages.len(), expanded.contains(path)));
+                            ListItem::new(project_line(
+                                *idx,
+                                &projects[*idx],
+                                grouped,
+                                installed.as_ref(),
+                                workspace,
+                                missing.contains(path),
+                            ))
+                        }
+                        Row::Package { project, package } => {
+                            let root = &projects[*project].path;
+                            ListItem::new(package_line(&workspaces[root][*package], root, grouped))
                         }
-
-                        ListItem::new(Line::from(spans))
                     })
                     .collect()
             };
 
+            let title = if grouped {
+                "Projects by tag"
+            } else {
+                "Projects"
+            };
             let list_title_text = if projects.is_empty() {
-                " Projects (No projects yet) ".to_string()
+                format!(" {} (No projects yet) ", title)
             } else if search_mode && !search_query.is_empty() {
-                format!(" Projects ({}/{}) ", filtered_indices.len(), projects.len())
+                format!(
+                    " {} ({}/{}) ",
+                    title,
+                    filtered_indices.len(),
+                    projects.len()
+                )
             } else {
-                " Projects ".to_string()
+                format!(" {} ", title)
             };
 
             let list = List::new(items)
@@ -202,88 +682,109 @@ where
                 )
                 .highlight_symbol("❯ "); // Simple, modern arrow
 
-            f.render_stateful_widget(list, content_layout[content_idx], &mut list_state);
-
-            // Footer with controls
-            let footer_text = if search_mode {
-                vec![
-                    Span::styled(
-                        "ESC",
-                        Style::default()
-                            .fg(HIGHLIGHT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" exit search", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "↑↓",
-                        Style::default()
-                            .fg(HIGHLIGHT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" navigate", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "ENTER",
-                        Style::default()
-                            .fg(ACCENT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" open", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "Q",
+            if let Some(panel) = &panel {
+                let project = &projects[panel.project];
+                let items: Vec<ListItem> = match &panel.error {
+                    Some(error) => vec![ListItem::new(Span::styled(
+                        error.as_str(),
                         Style::default()
                             .fg(ERROR_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" quit", Style::default().fg(TEXT_COLOR)),
-                ]
-            } else {
-                vec![
-                    Span::styled(
-                        "↑↓",
-                        Style::default()
-                            .fg(HIGHLIGHT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" navigate", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "ENTER",
-                        Style::default()
-                            .fg(ACCENT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" open", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "A",
-                        Style::default()
-                            .fg(HIGHLIGHT_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" add", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "/",
+                            .add_modifier(Modifier::ITALIC),
+                    ))],
+                    None => panel
+                        .entries
+                        .iter()
+                        .map(|(name, description, run)| {
+                            ListItem::new(script_line(name, description, run))
+                        })
+                        .collect(),
+                };
+                let name = match &panel.package {
+                    Some(package) => format!("{} › {}", project.name, package.name),
+                    None => project.name.clone(),
+                };
+                let title = match project.package_manager {
+                    Some(manager) => format!(" Run: {} [{}] ", name, manager),
+                    None => format!(" Run: {} ", name),
+                };
+                let scripts = List::new(items)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                            .title(title)
+                            .title_style(
+                                Style::default()
+                                    .fg(HIGHLIGHT_COLOR)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                    )
+                    .highlight_style(
                         Style::default()
-                            .fg(HIGHLIGHT_COLOR)
+                            .fg(Color::Black)
+                            .bg(HIGHLIGHT_COLOR)
                             .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" search", Style::default().fg(TEXT_COLOR)),
-                    Span::raw(" | "),
-                    Span::styled(
-                        "Q",
-                        Style::default()
-                            .fg(ERROR_COLOR)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(" quit", Style::default().fg(TEXT_COLOR)),
-                ]
+                    )
+                    .highlight_symbol("❯ ");
+                panel_state.select(if panel.entries.is_empty() {
+                    None
+                } else {
+                    Some(panel.selected)
+                });
+                f.render_stateful_widget(scripts, content_layout[content_idx], &mut panel_state);
+            } else {
+                f.render_stateful_widget(list, content_layout[content_idx], &mut list_state);
+            }
+
+            // Footer with controls
+            let footer_text = if let Some(status) = &status {
+                Line::from(Span::styled(
+                    status.clone(),
+                    Style::default().fg(ERROR_COLOR),
+                ))
+            } else if panel.is_some() {
+                let mut hints = vec![
+                    ("↑↓", "navigate", HIGHLIGHT_COLOR),
+                    ("ENTER", "run in new terminal", ACCENT_COLOR),
+                ];
+                if options.here {
+                    hints.push(("H", "run here", ACCENT_COLOR));
+                }
+                hints.push(("ESC", "back", HIGHLIGHT_COLOR));
+                footer_line(&hints)
+            } else if search_mode {
+                footer_line(&[
+                    ("ESC", "exit search", HIGHLIGHT_COLOR),
+                    ("↑↓", "navigate", HIGHLIGHT_COLOR),
+                    ("ENTER", "open", ACCENT_COLOR),
+                    ("Q", "quit", ERROR_COLOR),
+                ])
+            } else {
+                let mut hints = vec![
+                    ("↑↓", "navigate", HIGHLIGHT_COLOR),
+                    ("ENTER", "open", ACCENT_COLOR),
+                ];
+                if options.here {
+                    hints.push(("H", "open here", ACCENT_COLOR));
+                }
+                hints.extend([
+                    ("A", "add", HIGHLIGHT_COLOR),
+                    ("P", "pin", HIGHLIGHT_COLOR),
+                    ("T", "tags", HIGHLIGHT_COLOR),
+                ]);
+                if options.scripts {
+                    hints.push(("S", "scripts & profiles", HIGHLIGHT_COLOR));
+                }
+                hints.extend([
+                    ("G", "group", HIGHLIGHT_COLOR),
+                    ("O", "order", HIGHLIGHT_COLOR),
+                    ("/", "search", HIGHLIGHT_COLOR),
+                    ("Q", "quit", ERROR_COLOR),
+                ]);
+                footer_line(&hints)
             };
 
-            let footer = Paragraph::new(Line::from(footer_text))
+            let footer = Paragraph::new(footer_text)
                 .alignment(Alignment::Center)
                 .block(
                     Block::default()
@@ -299,121 +800,298 @@ where
             f.render_widget(footer, main_layout[1]);
         })?;
 
-        if event::poll(std::time::Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
-                if search_mode {
-                    match key.code {
-                        KeyCode::Esc => {
-                            search_mode = false;
-                            search_query.clear();
-                            selected = 0;
-                        }
-                        KeyCode::Enter => {
-                            if let Some(&original_idx) = filtered_indices.get(selected) {
-                                if let Some(proj) = projects.get_mut(original_idx) {
-                                    open_cb(proj)?;
-                                    save_projects(projects)?;
-                                    selected = 0;
-                                }
+        if event::poll(std::time::Duration::from_millis(200))?
+            && let Event::Key(key) = event::read()?
+        {
+            status = None;
+            if let Some(current) = &mut panel {
+                match key.code {
+                    KeyCode::Down if current.selected + 1 < current.entries.len() => {
+                        current.selected += 1;
+                    }
+                    KeyCode::Up => current.selected = current.selected.saturating_sub(1),
+                    KeyCode::Esc | KeyCode::Char('q') => panel = None,
+                    KeyCode::Enter | KeyCode::Char('h') => {
+                        let open_in = if key.code == KeyCode::Enter {
+                            OpenIn::NewTerminal
+                        } else if options.here {
+                            OpenIn::Here
+                        } else {
+                            continue;
+                        };
+                        if let Some((_, _, run)) = current.entries.get(current.selected) {
+                            let idx = current.project;
+                            let run = run.clone();
+                            let dir = current.package.as_ref().map(|p| p.path.clone());
+                            panel = None;
+                            if projects[idx].is_missing() {
+                                status = Some(projects[idx].missing_error().to_string());
+                                continue;
                             }
-                        }
-                        KeyCode::Backspace => {
-                            search_query.pop();
-                            selected = 0;
-                        }
-                        KeyCode::Down => {
-                            if !filtered_indices.is_empty() {
-                                selected =
-                                    (selected + 1).min(filtered_indices.len().saturating_sub(1));
+                            let result =
+                                open_cb(&mut projects[idx], dir.as_deref(), Some(&run), open_in);
+                            let opened = dir.unwrap_or_else(|| projects[idx].path.clone());
+                            save_open(projects, sort, &mut installed, &mut missing)?;
+                            if let Ok(AfterOpen::Exit) = result {
+                                break;
                             }
-                        }
-                        KeyCode::Up => {
-                            if selected > 0 {
-                                selected -= 1;
+                            follow = Some(opened);
+                            if let Err(e) = result {
+                                status = Some(e.to_string());
                             }
                         }
-                        KeyCode::Char(c) => {
-                            search_query.push(c);
-                            selected = 0;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let selected_project = match rows.get(selected) {
+                Some(Row::Project(idx)) => Some(*idx),
+                _ => None,
+            };
+
+            // Keys that behave the same while searching
+            match key.code {
+                KeyCode::Down => {
+                    selected = step(&rows, selected, true);
+                    continue;
+                }
+                KeyCode::Up => {
+                    selected = step(&rows, selected, false);
+                    continue;
+                }
+                KeyCode::Enter | KeyCode::Char('h')
+                    if key.code == KeyCode::Enter || (options.here && !search_mode) =>
+                {
+                    let open_in = if key.code == KeyCode::Enter {
+                        OpenIn::NewTerminal
+                    } else {
+                        OpenIn::Here
+                    };
+                    let (idx, dir) = match rows.get(selected) {
+                        Some(Row::Group { tag, folded: f, .. })
+                            if open_in == OpenIn::NewTerminal =>
+                        {
+                            if *f {
+                                folded.remove(tag);
+                            } else {
+                                folded.insert(tag.clone());
+                            }
+                            continue;
                         }
-                        _ => {}
+                        Some(Row::Project(idx)) => (*idx, None),
+                        Some(Row::Package { project, package }) => (
+                            *project,
+                            Some(workspaces[&projects[*project].path][*package].path.clone()),
+                        ),
+                        _ => continue,
+                    };
+                    // Refuse up front instead of a shell failing on `cd`
+                    if projects[idx].is_missing() {
+                        status = Some(projects[idx].missing_error().to_string());
+                        missing.insert(projects[idx].path.clone());
+                        continue;
                     }
-                } else {
-                    match key.code {
-                        KeyCode::Char('q') => break,
-                        KeyCode::Char('/') => {
-                            search_mode = true;
-                            search_query.clear();
-                            selected = 0;
+
+                    let result = open_cb(&mut projects[idx], dir.as_deref(), None, open_in);
+                    let opened = dir.unwrap_or_else(|| projects[idx].path.clone());
+                    save_open(projects, sort, &mut installed, &mut missing)?;
+                    if let Ok(AfterOpen::Exit) = result {
+                        break;
+                    }
+                    follow = Some(opened);
+                    // E.g. no terminal emulator, where opening here still works
+                    if let Err(e) = result {
+                        status = Some(e.to_string());
+                    }
+                    continue;
+                }
+                KeyCode::Right => {
+                    if let Some(Row::Project(idx)) = rows.get(selected)
+                        && workspaces.contains_key(&projects[*idx].path)
+                    {
+                        expanded.insert(projects[*idx].path.clone());
+                    }
+                    continue;
+                }
+                KeyCode::Left => {
+                    let project = match rows.get(selected) {
+                        Some(Row::Project(idx)) => *idx,
+                        Some(Row::Package { project, .. }) => *project,
+                        _ => continue,
+                    };
+                    let path = projects[project].path.clone();
+                    if expanded.remove(&path) {
+                        follow = Some(path);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            if search_mode {
+                match key.code {
+                    KeyCode::Esc => {
+                        search_mode = false;
+                        search_query.clear();
+                        selected = 0;
+                    }
+                    KeyCode::Backspace => {
+                        search_query.pop();
+                        selected = 0;
+                    }
+                    KeyCode::Char(c) => {
+                        search_query.push(c);
+                        selected = 0;
+                    }
+                    _ => {}
+                }
+            } else {
+                match key.code {
+                    KeyCode::Char('q') => break,
+                    KeyCode::Char('/') => {
+                        search_mode = true;
+                        search_query.clear();
+                        selected = 0;
+                    }
+                    KeyCode::Char('o') => {
+                        sort = sort.next();
+                        sort_projects(projects, sort);
+                        selected = 0;
+                    }
+                    KeyCode::Char('p') => {
+                        let Some(idx) = selected_project else {
+                            continue;
+                        };
+                        if projects[idx].pinned.is_some() {
+                            unpin_project(projects, idx);
+                        } else {
+                            pin_project(projects, idx, None);
                         }
-                        KeyCode::Down => {
-                            if !filtered_indices.is_empty() {
-                                selected =
-                                    (selected + 1).min(filtered_indices.len().saturating_sub(1));
-                            }
+                        follow = Some(projects[idx].path.clone());
+                        save_projects(projects)?;
+                        sort_projects(projects, sort);
+                    }
+                    KeyCode::Char('[') | KeyCode::Char(']') => {
+                        let Some(idx) = selected_project else {
+                            continue;
+                        };
+                        let Some(pos) = projects[idx].pinned else {
+                            continue;
+                        };
+                        let target = if key.code == KeyCode::Char('[') {
+                            pos.saturating_sub(1).max(1)
+                        } else {
+                            pos + 1
+                        };
+                        pin_project(projects, idx, Some(target));
+                        follow = Some(projects[idx].path.clone());
+                        save_projects(projects)?;
+                        sort_projects(projects, sort);
+                    }
+                    KeyCode::Char('s') if options.scripts => match rows.get(selected) {
+                        Some(Row::Project(idx)) => {
+                            panel = Some(ScriptsPanel::new(*idx, &projects[*idx], None));
                         }
-                        KeyCode::Up => {
-                            if selected > 0 {
-                                selected -= 1;
-                            }
+                        Some(Row::Package { project, package }) => {
+                            let package = workspaces[&projects[*project].path][*package].clone();
+                            panel = Some(ScriptsPanel::new(
+                                *project,
+                                &projects[*project],
+                                Some(package),
+                            ));
                         }
-                        KeyCode::Enter => {
-                            if let Some(&original_idx) = filtered_indices.get(selected) {
-                                if let Some(proj) = projects.get_mut(original_idx) {
-                                    open_cb(proj)?;
-                                    save_projects(projects)?;
-                                    selected = 0;
+                        _ => {}
+                    },
+                    KeyCode::Char('g') => {
+                        grouped = !grouped;
+                        selected = 0;
+                    }
+                    KeyCode::Char('t') => {
+                        let Some(idx) = selected_project else {
+                            continue;
+                        };
+                        suspend(&mut terminal)?;
+
+                        let proj = &projects[idx];
+                        writeln!(terminal.backend_mut(), "\nEdit Tags: {}", proj.name)?;
+                        writeln!(terminal.backend_mut(), "---------------")?;
+                        let current = if proj.tags.is_empty() {
+                            "none".to_string()
+                        } else {
+                            proj.tags.join(", ")
+                        };
+                        writeln!(terminal.backend_mut(), "Current tags: {}", current)?;
+                        let input = prompt(
+                            &mut terminal,
+                            "Tags to add, prefix with - to remove (e.g. client-a -old): ",
+                        )?;
+
+                        let (remove, add): (Vec<String>, Vec<String>) = input
+                            .split([' ', ','])
+                            .filter(|t| !t.is_empty())
+                            .map(str::to_string)
+                            .partition(|t| t.starts_with('-'));
+                        let remove: Vec<String> =
+                            remove.iter().map(|t| t[1..].to_string()).collect();
+
+                        let proj = &mut projects[idx];
+                        proj.remove_tags(&remove);
+                        proj.add_tags(&add);
+                        follow = Some(proj.path.clone());
+                        save_projects(projects)?;
+                        sort_projects(projects, sort);
+
+                        resume(&mut terminal)?;
+                    }
+                    KeyCode::Char('a') => {
+                        suspend(&mut terminal)?;
+
+                        writeln!(terminal.backend_mut(), "\nAdd New Project")?;
+                        writeln!(terminal.backend_mut(), "---------------")?;
+                        let name = prompt(
+                            &mut terminal,
+                            "Project name (empty for the directory name): ",
+                        )?;
+                        let path = prompt(&mut terminal, "Project path: ")?;
+
+                        // The same checks as `nodash add`
+                        let name = Some(name.as_str()).filter(|n| !n.is_empty());
+                        match add_project(projects, Path::new(&path), name) {
+                            Ok(project) => {
+                                let packages = package::workspace_packages(&project.path);
+                                if !packages.is_empty() {
+                                    workspaces.insert(project.path.clone(), packages);
+                                }
+                                let node_version = project.node_version.clone();
+                                let node_source = project.node_source.clone().unwrap_or_default();
+
+                                save_projects(projects)?;
+                                sort_projects(projects, sort);
+                                selected = 0;
+
+                                writeln!(terminal.backend_mut(), "\nProject added.")?;
+                                if let Some(version) = node_version {
+                                    writeln!(
+                                        terminal.backend_mut(),
+                                        "Node version detected: {} (from {})",
+                                        version,
+                                        node_source
+                                    )?;
+                                } else {
+                                    writeln!(terminal.backend_mut(), "No Node version specified.")?;
                                 }
                             }
-                        }
-                        KeyCode::Char('a') => {
-                            terminal::disable_raw_mode()?;
-                            execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
-                            terminal.show_cursor()?;
-
-                            println!("\nAdd New Project");
-                            println!("---------------");
-                            let mut name = String::new();
-                            let mut path = String::new();
-
-                            print!("Project name: ");
-                            io::stdout().flush()?;
-                            io::stdin().read_line(&mut name)?;
-
-                            print!("Project path: ");
-                            io::stdout().flush()?;
-                            io::stdin().read_line(&mut path)?;
-
-                            let project_path = PathBuf::from(path.trim());
-                            let node_version = detect_node_version(&project_path).ok();
-
-                            let project = Project {
-                                name: name.trim().to_string(),
-                                path: project_path.clone(),
-                                last_opened: None,
-                                node_version: node_version.clone(),
-                            };
-
-                            projects.push(project);
-                            save_projects(projects)?;
-                            selected = 0;
-
-                            println!("\nProject added.");
-                            if let Some(version) = node_version {
-                                println!("Node version detected: {}", version);
-                            } else {
-                                println!("No .nvmrc file found.");
+                            Err(e) => {
+                                writeln!(terminal.backend_mut(), "\nCould not add project: {}", e)?;
                             }
-                            println!("\nPress Enter to return to dashboard...");
-                            let mut dummy = String::new();
-                            io::stdin().read_line(&mut dummy)?;
-
-                            terminal.clear()?;
-                            execute!(terminal.backend_mut(), terminal::EnterAlternateScreen)?;
-                            terminal::enable_raw_mode()?;
                         }
-                        _ => {}
+                        prompt(&mut terminal, "\nPress Enter to return to dashboard...")?;
+
+                        resume(&mut terminal)?;
                     }
+                    _ => {}
                 }
             }
         }
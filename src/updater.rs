@@ -30,14 +30,14 @@ pub fn check_for_update() -> io::Result<()> {
 
 fn get_latest_release_version() -> io::Result<String> {
     let output = Command::new("curl")
-        .args(&["-s", &format!("https://api.github.com/repos/{}/releases/latest", REPO)])
+        .args(["-s", &format!("https://api.github.com/repos/{}/releases/latest", REPO)])
         .output()?;
     if !output.status.success() {
-        return Err(io::Error::new(io::ErrorKind::Other, "Failed to fetch release info"));
+        return Err(io::Error::other("Failed to fetch release info"));
     }
 
     let release: Release = serde_json::from_slice(&output.stdout)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("JSON parse error: {}", e)))?;
+        .map_err(|e| io::Error::other(format!("JSON parse error: {}", e)))?;
     let version = release.tag_name
         .strip_prefix('v')
         .map(|s| s.to_string())
@@ -60,13 +60,13 @@ fn download_and_replace_binary(version: &str) -> io::Result<()> {
     let tmp_path = env::temp_dir().join("nodash-update");
 
     let status = Command::new("curl")
-        .args(&["-L", "-o"])
+        .args(["-L", "-o"])
         .arg(&tmp_path)
         .arg(&url)
         .status()?;
 
     if !status.success() {
-        return Err(io::Error::new(io::ErrorKind::Other, "Download failed"));
+        return Err(io::Error::other("Download failed"));
     }
 
     println!("🔐 Making binary executable...");
@@ -0,0 +1,114 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::ZellijMode;
+use crate::terminal::{Launch, TerminalLauncher, command_exists, run_quiet};
+
+// Layouts a repository can ship for its Zellij tab, first found wins
+const LAYOUT_FILES: &[&str] = &[".zellij/layout.kdl", "zellij.kdl"];
+
+/// Opens projects in the Zellij session nodash runs in, as a new tab or a
+/// new pane.
+pub struct Zellij {
+    pub mode: ZellijMode,
+    /// Use the layout file of the opened directory for new tabs.
+    pub layouts: bool,
+}
+
+// The shell with the launch's variables; Zellij panes cannot set any, so
+// `env` does
+fn shell_command(launch: &Launch) -> Vec<String> {
+    let mut command = Vec::new();
+    if !launch.env.is_empty() {
+        command.push("env".to_string());
+        for (key, value) in &launch.env {
+            command.push(format!("{}={}", key, value.to_string_lossy()));
+        }
+    }
+    command.extend(launch.shell.iter().cloned());
+    command
+}
+
+// A KDL string literal
+fn kdl_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// A one-pane layout running the shell, written next to the zsh shims
+fn write_layout(launch: &Launch) -> io::Result<PathBuf> {
+    let command = shell_command(launch);
+    let args: Vec<String> = command[1..].iter().map(|arg| kdl_string(arg)).collect();
+    let layout = format!(
+        "layout {{\n    pane command={} cwd={} {{\n        args {}\n    }}\n}}\n",
+        kdl_string(&command[0]),
+        kdl_string(&launch.cwd.display().to_string()),
+        args.join(" ")
+    );
+
+    let path = env::temp_dir().join(format!(
+        "nodash-zellij-{}-{}.kdl",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+    ));
+    fs::write(&path, layout)?;
+    Ok(path)
+}
+
+fn project_layout(dir: &Path) -> Option<PathBuf> {
+    LAYOUT_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.is_file())
+}
+
+impl TerminalLauncher for Zellij {
+    fn name(&self) -> &str {
+        "zellij"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("zellij")
+    }
+
+    fn launch(&self, launch: &Launch) -> io::Result<()> {
+        let cwd = launch.cwd.display().to_string();
+        match self.mode {
+            ZellijMode::Pane => {
+                let mut cmd = Command::new("zellij");
+                cmd.args(["run", "--name", &launch.title, "--cwd", &cwd, "--"])
+                    .args(shell_command(launch));
+                run_quiet(&mut cmd)?;
+            }
+            _ => {
+                // A repository layout decides what its panes run, so it only
+                // fits plain opens; a script or profile needs the shell
+                let repo_layout =
+                    project_layout(&launch.cwd).filter(|_| self.layouts && launch.reusable);
+                let layout = match repo_layout {
+                    Some(layout) => layout,
+                    None => write_layout(launch)?,
+                };
+                let mut cmd = Command::new("zellij");
+                cmd.args(["action", "new-tab", "--name", &launch.title, "--cwd", &cwd])
+                    .arg("--layout")
+                    .arg(layout);
+                run_quiet(&mut cmd)?;
+            }
+        }
+        Ok(())
+    }
+}